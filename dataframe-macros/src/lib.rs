@@ -1,46 +1,222 @@
 extern crate proc_macro2;
 
+use darling::{ast, util::Ignored, Error, FromDeriveInput, FromField};
 use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
 use quote::quote;
-use syn::Data;
+use syn::{Ident, Index, Member};
 
-#[proc_macro_derive(ToRow)]
-pub fn derive_into_hash_map(item: TokenStream) -> TokenStream {
-    let input = syn::parse_macro_input!(item as syn::DeriveInput);
+#[derive(FromField)]
+#[darling(attributes(rowboat))]
+struct RowField {
+    ident: Option<Ident>,
+    ty: syn::Type,
+    #[darling(default)]
+    rename: Option<String>,
+    #[darling(default)]
+    skip: bool,
+    #[darling(default)]
+    flatten: bool,
+    #[darling(default)]
+    with: Option<syn::Path>,
+}
 
-    let struct_identifier = &input.ident;
+#[derive(FromDeriveInput)]
+#[darling(
+    attributes(rowboat),
+    supports(struct_named, struct_newtype, struct_tuple)
+)]
+struct RowStruct {
+    ident: Ident,
+    generics: syn::Generics,
+    data: ast::Data<Ignored, RowField>,
+}
 
-    match &input.data {
-        Data::Struct(syn::DataStruct { fields, .. }) => {
-            let mut to_row_impl = quote! {
-                let mut v = vec![];
-            };
-            let mut to_label_impl = quote! {
-                let mut v = vec![];
+// A field with its access path on the struct and its column label
+struct Field {
+    member: Member,
+    label: String,
+    opts: RowField,
+}
+
+struct Parsed {
+    ident: Ident,
+    generics: syn::Generics,
+    fields: Vec<Field>,
+    tuple: bool,
+}
+
+fn check(opts: &RowField) -> Result<(), Error> {
+    if opts.skip && (opts.rename.is_some() || opts.flatten || opts.with.is_some()) {
+        return Err(
+            Error::custom("`skip` cannot be combined with other rowboat attributes")
+                .with_span(&opts.ty),
+        );
+    }
+    if opts.flatten && (opts.rename.is_some() || opts.with.is_some()) {
+        return Err(
+            Error::custom("`flatten` cannot be combined with `rename` or `with`")
+                .with_span(&opts.ty),
+        );
+    }
+    Ok(())
+}
+
+fn parse(item: TokenStream) -> Result<Parsed, Error> {
+    let input: syn::DeriveInput = syn::parse(item)?;
+    let RowStruct {
+        ident,
+        generics,
+        data,
+    } = RowStruct::from_derive_input(&input)?;
+    let fields = data.take_struct().unwrap();
+    let tuple = fields.is_tuple();
+    let mut errors = Error::accumulator();
+    let fields = fields
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, opts)| {
+            errors.handle(check(&opts))?;
+            let (member, name) = match &opts.ident {
+                Some(ident) => (Member::Named(ident.clone()), ident.to_string()),
+                None => (Member::Unnamed(Index::from(i)), i.to_string()),
             };
-            for field in fields {
-                let identifier = field.ident.as_ref().unwrap();
-                to_row_impl.extend(quote! {
-                    v.push(self.#identifier.ref_to_cell());
-                });
-                to_label_impl.extend(quote! {
-                    v.push(stringify!(#identifier).to_string());
-                })
+            Some(Field {
+                member,
+                label: opts.rename.clone().unwrap_or(name),
+                opts,
+            })
+        })
+        .collect();
+    errors.finish()?;
+    Ok(Parsed {
+        ident,
+        generics,
+        fields,
+        tuple,
+    })
+}
+
+#[proc_macro_derive(ToRow, attributes(rowboat))]
+pub fn derive_into_hash_map(item: TokenStream) -> TokenStream {
+    let Parsed {
+        ident: struct_identifier,
+        generics,
+        fields,
+        ..
+    } = match parse(item) {
+        Ok(parsed) => parsed,
+        Err(e) => return e.write_errors().into(),
+    };
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let mut to_row_impl = quote! {
+        let mut v = vec![];
+    };
+    let mut to_label_impl = quote! {
+        let mut v = vec![];
+    };
+    for Field {
+        member,
+        label,
+        opts,
+    } in fields
+    {
+        if opts.skip {
+            continue;
+        }
+        if opts.flatten {
+            to_row_impl.extend(quote! {
+                v.extend(ToRow::to_row(&self.#member));
+            });
+            to_label_impl.extend(quote! {
+                v.extend(ToRow::labels(&self.#member));
+            });
+            continue;
+        }
+        to_row_impl.extend(match &opts.with {
+            Some(with) => quote! {
+                v.push(#with::to_cell(&self.#member));
+            },
+            None => quote! {
+                v.push(self.#member.ref_to_cell());
+            },
+        });
+        to_label_impl.extend(quote! {
+            v.push(#label.to_string());
+        })
+    }
+    quote! {
+        impl #impl_generics ToRow for #struct_identifier #ty_generics #where_clause {
+            fn to_row(&self) -> Vec<Cell> {
+                #to_row_impl
+                v
             }
-            quote! {
-                impl ToRow for #struct_identifier {
-                    fn to_row(&self) -> Vec<Cell> {
-                        #to_row_impl
-                        v
-                    }
-                    fn labels(&self) -> Vec<String> {
-                        #to_label_impl
-                        v
+            fn labels(&self) -> Vec<String> {
+                #to_label_impl
+                v
+            }
+        }
+    }
+    .into()
+}
+
+#[proc_macro_derive(FromRow, attributes(rowboat))]
+pub fn derive_from_hash_map(item: TokenStream) -> TokenStream {
+    let Parsed {
+        ident: struct_identifier,
+        generics,
+        fields,
+        tuple,
+    } = match parse(item) {
+        Ok(parsed) => parsed,
+        Err(e) => return e.write_errors().into(),
+    };
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let values: Vec<TokenStream2> = fields
+        .iter()
+        .map(
+            |Field {
+                 label, opts, ..
+             }| {
+                if opts.skip {
+                    return quote! { Default::default() };
+                }
+                if opts.flatten {
+                    return quote! { FromRow::from_row(row)? };
+                }
+                let convert = match &opts.with {
+                    Some(with) => quote! { #with::from_cell(cell) },
+                    None => quote! { FromCell::from_cell(cell) },
+                };
+                quote! {
+                    match row.get(#label) {
+                        Some(cell) => match #convert {
+                            Ok(val) => val,
+                            Err(e) => {
+                                return Err(Error::new(format!("Column `{}`: {}", #label, e)))
+                            }
+                        },
+                        None => return Err(Error::new(format!("Missing column `{}`", #label))),
                     }
                 }
+            },
+        )
+        .collect();
+    let construct = match tuple {
+        true => quote! { #struct_identifier(#(#values),*) },
+        false => {
+            let members = fields.iter().map(|field| &field.member);
+            quote! { #struct_identifier { #(#members: #values),* } }
+        }
+    };
+    quote! {
+        impl #impl_generics FromRow for #struct_identifier #ty_generics #where_clause {
+            fn from_row(row: &::std::collections::HashMap<String, Cell>) -> Result<Self, Error> {
+                Ok(#construct)
             }
         }
-        _ => unimplemented!(),
     }
     .into()
 }
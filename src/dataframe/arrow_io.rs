@@ -0,0 +1,212 @@
+use crate::{cell::*, column::Col, util::Error};
+use arrow_array::{
+    cast::AsArray, types::*, Array, ArrayRef, ArrowPrimitiveType, BooleanArray, Float64Array,
+    Int64Array, RecordBatch, RecordBatchOptions, StringArray, TimestampMicrosecondArray,
+    UInt64Array,
+};
+use arrow_ipc::{reader::FileReader, writer::FileWriter};
+use arrow_schema::{ArrowError, DataType, Field, Schema, SchemaRef, TimeUnit};
+use std::{fs::File, sync::Arc};
+
+fn arrow_err(e: ArrowError) -> Error {
+    Error::new(e.to_string())
+}
+
+fn to_array(col: &Col) -> (DataType, ArrayRef) {
+    let cells = col.iter();
+    match col.typed().zero() {
+        Cell::Int(_) => (
+            DataType::Int64,
+            Arc::new(Int64Array::from_iter(cells.map(|cell| match cell {
+                Cell::Int(x) => Some(x),
+                _ => None,
+            }))),
+        ),
+        Cell::Uint(_) => (
+            DataType::UInt64,
+            Arc::new(UInt64Array::from_iter(cells.map(|cell| match cell {
+                Cell::Uint(x) => Some(x),
+                _ => None,
+            }))),
+        ),
+        Cell::Float(_) => (
+            DataType::Float64,
+            Arc::new(Float64Array::from_iter(cells.map(|cell| match cell {
+                Cell::Float(x) => Some(x),
+                _ => None,
+            }))),
+        ),
+        Cell::Bool(_) => (
+            DataType::Boolean,
+            Arc::new(BooleanArray::from_iter(cells.map(|cell| match cell {
+                Cell::Bool(x) => Some(x),
+                _ => None,
+            }))),
+        ),
+        Cell::Str(_) => (
+            DataType::Utf8,
+            Arc::new(StringArray::from_iter(cells.map(|cell| match cell {
+                Cell::Str(x) => Some(x),
+                _ => None,
+            }))),
+        ),
+        _ => (
+            DataType::Timestamp(TimeUnit::Microsecond, None),
+            Arc::new(TimestampMicrosecondArray::from_iter(cells.map(
+                |cell| match cell {
+                    Cell::DateTime(x) => Some(x.and_utc().timestamp_micros()),
+                    _ => None,
+                },
+            ))),
+        ),
+    }
+}
+
+// Nulls become unset bits in each array's validity bitmap
+pub(crate) fn to_record_batch(cols: &[Col], length: usize) -> Result<RecordBatch, Error> {
+    let (fields, arrays): (Vec<Field>, Vec<ArrayRef>) = cols
+        .iter()
+        .map(|col| {
+            let (data_type, array) = to_array(col);
+            (Field::new(col.name(), data_type, true), array)
+        })
+        .unzip();
+    RecordBatch::try_new_with_options(
+        Arc::new(Schema::new(fields)),
+        arrays,
+        &RecordBatchOptions::new().with_row_count(Some(length)),
+    )
+    .map_err(arrow_err)
+}
+
+fn primitive<T: ArrowPrimitiveType>(
+    array: &dyn Array,
+    typed: Cell,
+    to_cell: impl Fn(T::Native) -> Cell,
+) -> (Cell, Vec<Cell>) {
+    let values = array
+        .as_primitive::<T>()
+        .iter()
+        .map(|x| match x {
+            Some(x) => to_cell(x),
+            None => typed.null(),
+        })
+        .collect();
+    (typed, values)
+}
+
+fn date_times<T: ArrowTemporalType>(
+    array: &dyn Array,
+    name: &str,
+) -> Result<(Cell, Vec<Cell>), Error>
+where
+    i64: From<T::Native>,
+{
+    let array = array.as_primitive::<T>();
+    let typed = null_date().zero();
+    let mut values = Vec::with_capacity(array.len());
+    for i in 0..array.len() {
+        if array.is_null(i) {
+            values.push(typed.null());
+            continue;
+        }
+        match array.value_as_datetime(i) {
+            Some(x) => values.push(Cell::DateTime(x)),
+            None => {
+                return Err(Error::new(format!(
+                    "Value out of range for column `{name}`"
+                )))
+            }
+        }
+    }
+    Ok((typed, values))
+}
+
+fn from_array(array: &dyn Array, field: &Field) -> Result<(Cell, Vec<Cell>), Error> {
+    let name = field.name();
+    Ok(match field.data_type() {
+        DataType::Int8 => primitive::<Int8Type>(array, Cell::Int(0), |x| Cell::Int(x.into())),
+        DataType::Int16 => primitive::<Int16Type>(array, Cell::Int(0), |x| Cell::Int(x.into())),
+        DataType::Int32 => primitive::<Int32Type>(array, Cell::Int(0), |x| Cell::Int(x.into())),
+        DataType::Int64 => primitive::<Int64Type>(array, Cell::Int(0), Cell::Int),
+        DataType::UInt8 => primitive::<UInt8Type>(array, Cell::Uint(0), |x| Cell::Uint(x.into())),
+        DataType::UInt16 => primitive::<UInt16Type>(array, Cell::Uint(0), |x| Cell::Uint(x.into())),
+        DataType::UInt32 => primitive::<UInt32Type>(array, Cell::Uint(0), |x| Cell::Uint(x.into())),
+        DataType::UInt64 => primitive::<UInt64Type>(array, Cell::Uint(0), Cell::Uint),
+        DataType::Float32 => {
+            primitive::<Float32Type>(array, Cell::Float(0.0), |x| Cell::Float(x.into()))
+        }
+        DataType::Float64 => primitive::<Float64Type>(array, Cell::Float(0.0), Cell::Float),
+        DataType::Boolean => {
+            let typed = Cell::Bool(false);
+            let values = array
+                .as_boolean()
+                .iter()
+                .map(|x| match x {
+                    Some(x) => Cell::Bool(x),
+                    None => typed.null(),
+                })
+                .collect();
+            (typed, values)
+        }
+        DataType::Utf8 | DataType::LargeUtf8 => {
+            let typed = Cell::Str(String::new());
+            let to_cell = |x: Option<&str>| match x {
+                Some(x) => Cell::Str(x.to_string()),
+                None => typed.null(),
+            };
+            let values = match field.data_type() {
+                DataType::Utf8 => array.as_string::<i32>().iter().map(to_cell).collect(),
+                _ => array.as_string::<i64>().iter().map(to_cell).collect(),
+            };
+            (typed, values)
+        }
+        // Zoned timestamps are read as naive UTC
+        DataType::Timestamp(TimeUnit::Second, _) => date_times::<TimestampSecondType>(array, name)?,
+        DataType::Timestamp(TimeUnit::Millisecond, _) => {
+            date_times::<TimestampMillisecondType>(array, name)?
+        }
+        DataType::Timestamp(TimeUnit::Microsecond, _) => {
+            date_times::<TimestampMicrosecondType>(array, name)?
+        }
+        DataType::Timestamp(TimeUnit::Nanosecond, _) => {
+            date_times::<TimestampNanosecondType>(array, name)?
+        }
+        DataType::Date32 => date_times::<Date32Type>(array, name)?,
+        DataType::Date64 => date_times::<Date64Type>(array, name)?,
+        data_type => {
+            return Err(Error::new(format!(
+                "Unsupported Arrow type {data_type} in column `{name}`"
+            )))
+        }
+    })
+}
+
+pub(crate) fn from_record_batch(batch: &RecordBatch) -> Result<Vec<Col>, Error> {
+    batch
+        .schema()
+        .fields()
+        .iter()
+        .zip(batch.columns())
+        .map(|(field, array)| {
+            let (typed, values) = from_array(array.as_ref(), field)?;
+            Ok(Col::build(field.name().to_string(), values, typed))
+        })
+        .collect()
+}
+
+pub(crate) fn write_ipc(file: File, batch: &RecordBatch) -> Result<(), Error> {
+    let mut writer = FileWriter::try_new(file, &batch.schema()).map_err(arrow_err)?;
+    writer.write(batch).map_err(arrow_err)?;
+    writer.finish().map_err(arrow_err)
+}
+
+// Batches are returned alongside the schema so files without batches still have columns
+pub(crate) fn read_ipc(file: File) -> Result<(SchemaRef, Vec<RecordBatch>), Error> {
+    let reader = FileReader::try_new(file, None).map_err(arrow_err)?;
+    let schema = reader.schema();
+    let batches = reader
+        .collect::<Result<Vec<RecordBatch>, ArrowError>>()
+        .map_err(arrow_err)?;
+    Ok((schema, batches))
+}
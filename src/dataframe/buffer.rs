@@ -0,0 +1,209 @@
+use crate::cell::Cell;
+use chrono::NaiveDateTime;
+
+// One bit per row, backs bool columns and every column's validity
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Bitmap {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl Bitmap {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Bitmap {
+            words: Vec::with_capacity(capacity.div_ceil(64)),
+            len: 0,
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    pub fn get(&self, i: usize) -> bool {
+        (self.words[i / 64] >> (i % 64)) & 1 == 1
+    }
+    pub fn set(&mut self, i: usize, val: bool) {
+        match val {
+            true => self.words[i / 64] |= 1 << (i % 64),
+            false => self.words[i / 64] &= !(1 << (i % 64)),
+        }
+    }
+    pub fn push(&mut self, val: bool) {
+        if self.len.is_multiple_of(64) {
+            self.words.push(0);
+        }
+        self.len += 1;
+        self.set(self.len - 1, val);
+    }
+    pub fn extend(&mut self, other: &Bitmap) {
+        (0..other.len).for_each(|i| self.push(other.get(i)));
+    }
+}
+
+// Strings packed into one allocation, slot `i` is `data[start..start + len]` of `spans[i]`.
+// Overwrites reuse the slot when the new value fits and append otherwise, `dead` counts
+// the bytes no span points at so the arena can compact once they make up half of it.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct StrArena {
+    data: String,
+    spans: Vec<(usize, usize)>,
+    dead: usize,
+}
+
+impl StrArena {
+    pub fn with_capacity(capacity: usize) -> Self {
+        StrArena {
+            data: String::new(),
+            spans: Vec::with_capacity(capacity),
+            dead: 0,
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.spans.len()
+    }
+    pub fn get(&self, i: usize) -> &str {
+        let (start, len) = self.spans[i];
+        &self.data[start..start + len]
+    }
+    pub fn push(&mut self, val: &str) {
+        self.spans.push((self.data.len(), val.len()));
+        self.data.push_str(val);
+    }
+    pub fn set(&mut self, i: usize, val: &str) {
+        let (start, len) = self.spans[i];
+        if val.len() <= len {
+            // Padding the unused tail keeps `data` valid UTF-8
+            let padded = val.to_string() + &"\0".repeat(len - val.len());
+            self.data.replace_range(start..start + len, &padded);
+            self.spans[i] = (start, val.len());
+            self.dead += len - val.len();
+        } else {
+            self.spans[i] = (self.data.len(), val.len());
+            self.data.push_str(val);
+            self.dead += len;
+        }
+        if self.dead > self.data.len() / 2 {
+            self.compact();
+        }
+    }
+    fn compact(&mut self) {
+        let mut arena = StrArena::with_capacity(self.len());
+        (0..self.len()).for_each(|i| arena.push(self.get(i)));
+        *self = arena;
+    }
+}
+
+// Values of a single column type, null slots hold the type's default value
+#[derive(Debug, Clone)]
+pub(crate) enum Buffer {
+    Int(Vec<i64>),
+    Uint(Vec<u64>),
+    Float(Vec<f64>),
+    Bool(Bitmap),
+    Str(StrArena),
+    DateTime(Vec<NaiveDateTime>),
+}
+
+impl Buffer {
+    pub fn new(typed: &Cell, capacity: usize) -> Self {
+        match typed.zero() {
+            Cell::Int(_) => Buffer::Int(Vec::with_capacity(capacity)),
+            Cell::Uint(_) => Buffer::Uint(Vec::with_capacity(capacity)),
+            Cell::Float(_) => Buffer::Float(Vec::with_capacity(capacity)),
+            Cell::Bool(_) => Buffer::Bool(Bitmap::with_capacity(capacity)),
+            Cell::Str(_) => Buffer::Str(StrArena::with_capacity(capacity)),
+            _ => Buffer::DateTime(Vec::with_capacity(capacity)),
+        }
+    }
+    pub fn get(&self, i: usize) -> Cell {
+        match self {
+            Buffer::Int(v) => Cell::Int(v[i]),
+            Buffer::Uint(v) => Cell::Uint(v[i]),
+            Buffer::Float(v) => Cell::Float(v[i]),
+            Buffer::Bool(v) => Cell::Bool(v.get(i)),
+            Buffer::Str(v) => Cell::Str(v.get(i).to_string()),
+            Buffer::DateTime(v) => Cell::DateTime(v[i]),
+        }
+    }
+    // Nulls push the default value, returns false if the cell is of another type
+    pub fn push(&mut self, cell: &Cell) -> bool {
+        match (self, cell) {
+            (Buffer::Int(v), Cell::Int(x)) => v.push(*x),
+            (Buffer::Uint(v), Cell::Uint(x)) => v.push(*x),
+            (Buffer::Float(v), Cell::Float(x)) => v.push(*x),
+            (Buffer::Bool(v), Cell::Bool(x)) => v.push(*x),
+            (Buffer::Str(v), Cell::Str(x)) => v.push(x),
+            (Buffer::DateTime(v), Cell::DateTime(x)) => v.push(*x),
+            (buffer, Cell::Null(_)) => buffer.push_default(),
+            _ => return false,
+        }
+        true
+    }
+    fn push_default(&mut self) {
+        match self {
+            Buffer::Int(v) => v.push(0),
+            Buffer::Uint(v) => v.push(0),
+            Buffer::Float(v) => v.push(0.0),
+            Buffer::Bool(v) => v.push(false),
+            Buffer::Str(v) => v.push(""),
+            Buffer::DateTime(v) => v.push(NaiveDateTime::default()),
+        }
+    }
+    pub fn set(&mut self, i: usize, cell: &Cell) -> bool {
+        match (self, cell) {
+            (Buffer::Int(v), Cell::Int(x)) => v[i] = *x,
+            (Buffer::Uint(v), Cell::Uint(x)) => v[i] = *x,
+            (Buffer::Float(v), Cell::Float(x)) => v[i] = *x,
+            (Buffer::Bool(v), Cell::Bool(x)) => v.set(i, *x),
+            (Buffer::Str(v), Cell::Str(x)) => v.set(i, x),
+            (Buffer::DateTime(v), Cell::DateTime(x)) => v[i] = *x,
+            // The old value stays in the null slot
+            (_, Cell::Null(_)) => (),
+            _ => return false,
+        }
+        true
+    }
+    pub fn extend(&mut self, other: &Buffer) -> bool {
+        match (self, other) {
+            (Buffer::Int(v), Buffer::Int(o)) => v.extend(o),
+            (Buffer::Uint(v), Buffer::Uint(o)) => v.extend(o),
+            (Buffer::Float(v), Buffer::Float(o)) => v.extend(o),
+            (Buffer::Bool(v), Buffer::Bool(o)) => v.extend(o),
+            (Buffer::Str(v), Buffer::Str(o)) => (0..o.len()).for_each(|i| v.push(o.get(i))),
+            (Buffer::DateTime(v), Buffer::DateTime(o)) => v.extend(o),
+            _ => return false,
+        }
+        true
+    }
+    // `None` indices become default value slots for the caller to mark null
+    pub fn gather(&self, indices: &[Option<usize>]) -> Buffer {
+        match self {
+            Buffer::Int(v) => Buffer::Int(indices.iter().map(|i| i.map_or(0, |i| v[i])).collect()),
+            Buffer::Uint(v) => {
+                Buffer::Uint(indices.iter().map(|i| i.map_or(0, |i| v[i])).collect())
+            }
+            Buffer::Float(v) => {
+                Buffer::Float(indices.iter().map(|i| i.map_or(0.0, |i| v[i])).collect())
+            }
+            Buffer::Bool(v) => {
+                let mut bits = Bitmap::with_capacity(indices.len());
+                indices
+                    .iter()
+                    .for_each(|i| bits.push(i.is_some_and(|i| v.get(i))));
+                Buffer::Bool(bits)
+            }
+            Buffer::Str(v) => {
+                let mut arena = StrArena::with_capacity(indices.len());
+                indices
+                    .iter()
+                    .for_each(|i| arena.push(i.map_or("", |i| v.get(i))));
+                Buffer::Str(arena)
+            }
+            Buffer::DateTime(v) => Buffer::DateTime(
+                indices
+                    .iter()
+                    .map(|i| i.map_or(NaiveDateTime::default(), |i| v[i]))
+                    .collect(),
+            ),
+        }
+    }
+}
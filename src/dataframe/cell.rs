@@ -1,6 +1,11 @@
+use crate::util::Error;
 use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+use std::mem;
 
-#[derive(PartialEq, Clone, PartialOrd, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum Cell {
     Int(i64),
     Uint(u64),
@@ -10,6 +15,85 @@ pub enum Cell {
     DateTime(NaiveDateTime),
     Null(Box<Cell>),
 }
+
+// Floats compare by value with every NaN equal to itself and -0.0 equal to 0.0, so `Cell`
+// can be a hash key. Variants never equal each other, nulls are equal when their types are.
+fn float_bits(x: f64) -> u64 {
+    if x.is_nan() {
+        f64::NAN.to_bits()
+    } else if x == 0.0 {
+        0
+    } else {
+        x.to_bits()
+    }
+}
+impl PartialEq for Cell {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Cell::Int(x), Cell::Int(y)) => x == y,
+            (Cell::Uint(x), Cell::Uint(y)) => x == y,
+            (Cell::Str(x), Cell::Str(y)) => x == y,
+            (Cell::Bool(x), Cell::Bool(y)) => x == y,
+            (Cell::Float(x), Cell::Float(y)) => float_bits(*x) == float_bits(*y),
+            (Cell::DateTime(x), Cell::DateTime(y)) => x == y,
+            (Cell::Null(x), Cell::Null(y)) => x == y,
+            _ => false,
+        }
+    }
+}
+impl Eq for Cell {}
+// Ordered like `PartialEq` compares: variants in declaration order, every NaN equal and
+// above the other floats, -0.0 equal to 0.0
+impl Ord for Cell {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Cell::Int(x), Cell::Int(y)) => x.cmp(y),
+            (Cell::Uint(x), Cell::Uint(y)) => x.cmp(y),
+            (Cell::Str(x), Cell::Str(y)) => x.cmp(y),
+            (Cell::Bool(x), Cell::Bool(y)) => x.cmp(y),
+            (Cell::Float(x), Cell::Float(y)) => match (x.is_nan(), y.is_nan()) {
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                (false, false) => x.partial_cmp(y).unwrap(),
+            },
+            (Cell::DateTime(x), Cell::DateTime(y)) => x.cmp(y),
+            (Cell::Null(x), Cell::Null(y)) => x.cmp(y),
+            _ => variant_rank(self).cmp(&variant_rank(other)),
+        }
+    }
+}
+impl PartialOrd for Cell {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+fn variant_rank(cell: &Cell) -> u8 {
+    match cell {
+        Cell::Int(_) => 0,
+        Cell::Uint(_) => 1,
+        Cell::Str(_) => 2,
+        Cell::Bool(_) => 3,
+        Cell::Float(_) => 4,
+        Cell::DateTime(_) => 5,
+        Cell::Null(_) => 6,
+    }
+}
+impl Hash for Cell {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        mem::discriminant(self).hash(state);
+        match self {
+            Cell::Int(x) => x.hash(state),
+            Cell::Uint(x) => x.hash(state),
+            Cell::Str(x) => x.hash(state),
+            Cell::Bool(x) => x.hash(state),
+            Cell::Float(x) => float_bits(*x).hash(state),
+            Cell::DateTime(x) => x.hash(state),
+            Cell::Null(x) => x.hash(state),
+        }
+    }
+}
+
 impl Cell {
     pub fn zero(&self) -> Self {
         match self {
@@ -283,6 +367,113 @@ impl<T: ToCell> From<T> for Cell {
     }
 }
 
+pub trait FromCell: Sized {
+    fn from_cell(cell: &Cell) -> Result<Self, Error>;
+}
+
+fn convert_err(cell: &Cell, to: &str) -> Error {
+    Error::new(format!("Cannot convert {} to {to}", cell.type_string()))
+}
+
+fn range_err(cell: &Cell, to: &str) -> Error {
+    Error::new(format!("Value {} out of range for {to}", cell.as_string()))
+}
+
+impl FromCell for u32 {
+    fn from_cell(cell: &Cell) -> Result<Self, Error> {
+        match cell {
+            Cell::Uint(x) => u32::try_from(*x).map_err(|_| range_err(cell, "u32")),
+            Cell::Int(x) => u32::try_from(*x).map_err(|_| range_err(cell, "u32")),
+            _ => Err(convert_err(cell, "u32")),
+        }
+    }
+}
+
+impl FromCell for u64 {
+    fn from_cell(cell: &Cell) -> Result<Self, Error> {
+        match cell {
+            Cell::Uint(x) => Ok(*x),
+            Cell::Int(x) => u64::try_from(*x).map_err(|_| range_err(cell, "u64")),
+            _ => Err(convert_err(cell, "u64")),
+        }
+    }
+}
+
+impl FromCell for i32 {
+    fn from_cell(cell: &Cell) -> Result<Self, Error> {
+        match cell {
+            Cell::Int(x) => i32::try_from(*x).map_err(|_| range_err(cell, "i32")),
+            Cell::Uint(x) => i32::try_from(*x).map_err(|_| range_err(cell, "i32")),
+            _ => Err(convert_err(cell, "i32")),
+        }
+    }
+}
+
+impl FromCell for i64 {
+    fn from_cell(cell: &Cell) -> Result<Self, Error> {
+        match cell {
+            Cell::Int(x) => Ok(*x),
+            Cell::Uint(x) => i64::try_from(*x).map_err(|_| range_err(cell, "i64")),
+            _ => Err(convert_err(cell, "i64")),
+        }
+    }
+}
+
+impl FromCell for f32 {
+    fn from_cell(cell: &Cell) -> Result<Self, Error> {
+        match cell {
+            Cell::Float(_) | Cell::Int(_) | Cell::Uint(_) => Ok(cell.to_float_val() as f32),
+            _ => Err(convert_err(cell, "f32")),
+        }
+    }
+}
+
+impl FromCell for f64 {
+    fn from_cell(cell: &Cell) -> Result<Self, Error> {
+        match cell {
+            Cell::Float(_) | Cell::Int(_) | Cell::Uint(_) => Ok(cell.to_float_val()),
+            _ => Err(convert_err(cell, "f64")),
+        }
+    }
+}
+
+impl FromCell for bool {
+    fn from_cell(cell: &Cell) -> Result<Self, Error> {
+        match cell {
+            Cell::Bool(x) => Ok(*x),
+            _ => Err(convert_err(cell, "bool")),
+        }
+    }
+}
+
+impl FromCell for String {
+    fn from_cell(cell: &Cell) -> Result<Self, Error> {
+        match cell {
+            Cell::Str(x) => Ok(x.clone()),
+            _ => Err(convert_err(cell, "String")),
+        }
+    }
+}
+
+impl FromCell for NaiveDateTime {
+    fn from_cell(cell: &Cell) -> Result<Self, Error> {
+        match cell {
+            Cell::DateTime(x) => Ok(*x),
+            _ => Err(convert_err(cell, "NaiveDateTime")),
+        }
+    }
+}
+
+// Nulls of any type become `None`, non-null cells convert as `T`
+impl<T: FromCell> FromCell for Option<T> {
+    fn from_cell(cell: &Cell) -> Result<Self, Error> {
+        match cell {
+            Cell::Null(_) => Ok(None),
+            _ => T::from_cell(cell).map(Some),
+        }
+    }
+}
+
 pub struct Timestamp(pub i32, pub u32, pub u32, pub u32, pub u32, pub u32);
 
 impl ToCell for Timestamp {
@@ -366,6 +557,40 @@ pub fn cell_to_type_check(cell: &Cell) -> fn(&Cell) -> bool {
     }
 }
 
+// Casts cells of mixed types to one column type with its zero. Int and Uint become Int when
+// every value fits, other numeric mixes become Float and anything else falls back to Str.
+pub(crate) fn unify_cells(cells: Vec<Cell>) -> (Cell, Vec<Cell>) {
+    let zeros = cells.iter().map(|cell| cell.zero()).collect::<Vec<Cell>>();
+    let typed = match zeros.first() {
+        None => Cell::Str(String::new()),
+        Some(first) if zeros.iter().all(|zero| zero == first) => return (first.clone(), cells),
+        Some(_) if !zeros.iter().all(|zero| zero.is_num()) => Cell::Str(String::new()),
+        Some(_)
+            if cells.iter().all(|cell| match cell {
+                Cell::Int(_) => true,
+                Cell::Uint(x) => *x <= i64::MAX as u64,
+                Cell::Null(inner) => !cell_is_float(inner),
+                _ => false,
+            }) =>
+        {
+            Cell::Int(0)
+        }
+        Some(_) => Cell::Float(0.0),
+    };
+    let cells = cells
+        .into_iter()
+        .map(|cell| match (&typed, cell) {
+            (_, Cell::Null(_)) => typed.null(),
+            (Cell::Int(_), Cell::Uint(x)) => Cell::Int(x as i64),
+            (Cell::Float(_), cell) => cell.to_float(),
+            (Cell::Str(_), Cell::Str(x)) => Cell::Str(x),
+            (Cell::Str(_), cell) => Cell::Str(cell.as_string()),
+            (_, cell) => cell,
+        })
+        .collect();
+    (typed, cells)
+}
+
 pub fn null_int() -> Cell {
     Cell::Null(Box::new(Cell::Int(0)))
 }
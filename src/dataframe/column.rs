@@ -1,14 +1,74 @@
+use crate::buffer::{Bitmap, Buffer};
 use crate::util::Error;
 use crate::{cell::*, dataframe::Dataframe};
-use std::cmp::Ordering;
+use serde::{ser::SerializeStruct, Deserialize, Serialize, Serializer};
 use std::collections::{HashMap, HashSet};
+use std::ops::{Deref, DerefMut};
+use std::sync::OnceLock;
 
-#[derive(Debug, PartialEq, Clone)]
+// Values live in a typed buffer with a validity bit per row, `Cell`s are built on read.
+// `values()` builds a `Vec<Cell>` view once and keeps it until the next write, single
+// borrowed cells only build the chunk of `CHUNK_LEN` rows around them. `get` and `iter`
+// read the buffer directly.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "RawCol")]
 pub struct Col {
+    name: String,
+    typed: Cell,
+    data: Buffer,
+    validity: Bitmap,
+    #[serde(skip)]
+    cells: OnceLock<Vec<Cell>>,
+    #[serde(skip)]
+    chunks: OnceLock<Vec<OnceLock<Vec<Cell>>>>,
+}
+
+const CHUNK_LEN: usize = 1024;
+
+// Deserialized fields are checked against `typed` before becoming a Col
+#[derive(Deserialize)]
+struct RawCol {
     name: String,
     values: Vec<Cell>,
     typed: Cell,
-    type_check: fn(&Cell) -> bool,
+}
+
+impl TryFrom<RawCol> for Col {
+    type Error = Error;
+    fn try_from(raw: RawCol) -> Result<Self, Self::Error> {
+        let name = raw.name.clone();
+        match Col::try_build(raw.name, raw.values, raw.typed) {
+            Ok(col) => Ok(col),
+            Err(_) => Err(Error::new(format!("Invalid cell type in column `{name}`"))),
+        }
+    }
+}
+
+struct CellSeq<'a>(&'a Col);
+
+impl Serialize for CellSeq<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.0.iter())
+    }
+}
+
+impl Serialize for Col {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Col", 3)?;
+        state.serialize_field("name", &self.name)?;
+        state.serialize_field("values", &CellSeq(self))?;
+        state.serialize_field("typed", &self.typed)?;
+        state.end()
+    }
+}
+
+impl PartialEq for Col {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+            && self.typed == other.typed
+            && self.len() == other.len()
+            && (0..self.len()).all(|i| self.at(i) == other.at(i))
+    }
 }
 
 impl Col {
@@ -20,45 +80,165 @@ impl Col {
         if set.len() > 0 {
             z = set[0].ref_to_cell().zero();
         }
-        Col {
-            name: name,
-            values: set.into_iter().map(|val| val.to_cell()).collect(), // should validate all types match
-            type_check: cell_to_type_check(&z),
-            typed: z,
-        }
+        Col::build(name, set.into_iter().map(|val| val.to_cell()).collect(), z)
     }
+    // Values of another type become nulls, the column keeps `typed`
     pub fn build(name: String, values: Vec<Cell>, typed: Cell) -> Self {
         let type_check = cell_to_type_check(&typed);
-        Col {
+        let values = values
+            .into_iter()
+            .map(|cell| match type_check(&cell) {
+                true => cell,
+                false => typed.null(),
+            })
+            .collect();
+        Self::try_build(name, values, typed).expect("cells match their type")
+    }
+    // Values of another type widen the column instead, see `unify_cells`
+    pub(crate) fn build_unified(name: String, values: Vec<Cell>, typed: Cell) -> Self {
+        let (typed, values) = match values.iter().all(cell_to_type_check(&typed)) {
+            true => (typed, values),
+            false => unify_cells(values),
+        };
+        Self::try_build(name, values, typed).expect("unified cells match their type")
+    }
+    pub(crate) fn try_build(name: String, values: Vec<Cell>, typed: Cell) -> Result<Self, Error> {
+        let mut col = Col {
             name,
-            values,
+            data: Buffer::new(&typed, values.len()),
+            validity: Bitmap::with_capacity(values.len()),
             typed,
-            type_check,
+            cells: OnceLock::new(),
+            chunks: OnceLock::new(),
+        };
+        for cell in values.into_iter() {
+            col.push(cell)?;
         }
+        Ok(col)
     }
     pub fn apply(&mut self, f: fn(x: &mut Cell)) -> Result<(), Error> {
-        for cell in self.values.iter_mut() {
-            let mut new_cell = cell.clone();
+        for i in 0..self.len() {
+            let mut new_cell = self.at(i);
             f(&mut new_cell);
-            if (self.type_check)(&new_cell) {
-                *cell = new_cell
-            } else {
-                return Err(Error::new("Invalid cell type".to_string()));
-            }
+            self.set(i, new_cell)?;
         }
         Ok(())
     }
     pub fn check_type(&self, cell: &Cell) -> bool {
-        (self.type_check)(cell)
+        cell_to_type_check(&self.typed)(cell)
     }
-    pub fn values_mut(&mut self) -> &mut Vec<Cell> {
-        &mut self.values
+    pub fn len(&self) -> usize {
+        self.validity.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    pub fn is_null(&self, idx: usize) -> bool {
+        !self.validity.get(idx)
+    }
+    pub fn get(&self, idx: usize) -> Option<Cell> {
+        match idx < self.len() {
+            true => Some(self.at(idx)),
+            false => None,
+        }
+    }
+    // Unchecked `get` for internal row loops
+    pub(crate) fn at(&self, idx: usize) -> Cell {
+        match self.validity.get(idx) {
+            true => self.data.get(idx),
+            false => self.typed.null(),
+        }
+    }
+    pub fn iter(&self) -> impl Iterator<Item = Cell> + '_ {
+        (0..self.len()).map(|i| self.at(i))
     }
     pub fn values(&self) -> &Vec<Cell> {
-        &self.values
+        self.cells.get_or_init(|| self.iter().collect())
     }
-    pub fn take_values(self) -> Vec<Cell> {
-        self.values
+    // Borrowed cell at `idx`, from the `values()` view when it exists
+    pub(crate) fn cell(&self, idx: usize) -> Option<&Cell> {
+        if let Some(values) = self.cells.get() {
+            return values.get(idx);
+        }
+        if idx >= self.len() {
+            return None;
+        }
+        let chunks = self.chunks.get_or_init(|| {
+            (0..self.len().div_ceil(CHUNK_LEN))
+                .map(|_| OnceLock::new())
+                .collect()
+        });
+        let start = idx - idx % CHUNK_LEN;
+        let chunk = chunks[idx / CHUNK_LEN].get_or_init(|| {
+            (start..self.len().min(start + CHUNK_LEN))
+                .map(|i| self.at(i))
+                .collect()
+        });
+        chunk.get(idx - start)
+    }
+    fn clear_cache(&mut self) {
+        self.cells.take();
+        self.chunks.take();
+    }
+    // Edits are packed back into the buffer when the guard drops
+    pub fn values_mut(&mut self) -> ValuesMut<'_> {
+        let values = match self.cells.take() {
+            Some(values) => values,
+            None => self.iter().collect(),
+        };
+        ValuesMut { col: self, values }
+    }
+    pub fn take_values(mut self) -> Vec<Cell> {
+        match self.cells.take() {
+            Some(values) => values,
+            None => self.iter().collect(),
+        }
+    }
+    pub fn push(&mut self, cell: Cell) -> Result<(), Error> {
+        if !self.check_type(&cell) || !self.data.push(&cell) {
+            return Err(Error::new("Invalid cell type".to_string()));
+        }
+        self.clear_cache();
+        self.validity.push(!cell.is_null());
+        Ok(())
+    }
+    pub fn set(&mut self, idx: usize, cell: Cell) -> Result<(), Error> {
+        if idx >= self.len() {
+            return Err(Error::new("Index out of bounds".to_string()));
+        }
+        if !self.check_type(&cell) || !self.data.set(idx, &cell) {
+            return Err(Error::new("Invalid cell type".to_string()));
+        }
+        self.clear_cache();
+        self.validity.set(idx, !cell.is_null());
+        Ok(())
+    }
+    pub fn extend(&mut self, with: &Col) -> Result<(), Error> {
+        if !self.data.extend(&with.data) {
+            return Err(Error::new("Invalid cell type".to_string()));
+        }
+        self.clear_cache();
+        self.validity.extend(&with.validity);
+        Ok(())
+    }
+    // New column made of the rows at `indices`, in order
+    pub fn gather(&self, indices: &[usize]) -> Col {
+        self.gather_opt(&indices.iter().map(|i| Some(*i)).collect::<Vec<_>>())
+    }
+    // Like `gather` with `None` indices becoming nulls
+    pub(crate) fn gather_opt(&self, indices: &[Option<usize>]) -> Col {
+        let mut validity = Bitmap::with_capacity(indices.len());
+        indices
+            .iter()
+            .for_each(|i| validity.push(i.is_some_and(|i| self.validity.get(i))));
+        Col {
+            name: self.name.clone(),
+            typed: self.typed.clone(),
+            data: self.data.gather(indices),
+            validity,
+            cells: OnceLock::new(),
+            chunks: OnceLock::new(),
+        }
     }
     pub fn name(&self) -> &str {
         &self.name
@@ -70,12 +250,7 @@ impl Col {
         &self.typed
     }
     pub fn empty_from(&self) -> Col {
-        Col {
-            name: self.name.clone(),
-            values: vec![],
-            typed: self.typed.clone(),
-            type_check: cell_to_type_check(&self.typed),
-        }
+        Col::build(self.name.clone(), vec![], self.typed.clone())
     }
 
     pub fn describe(&self) -> Dataframe {
@@ -86,12 +261,12 @@ impl Col {
     }
 
     fn describe_object(&self) -> Dataframe {
-        if self.values.len() == 0 {
+        if self.is_empty() {
             return self.describe_with(vec![]);
         }
         let (_, unique, first_index, most) = self.top().unwrap();
         self.describe_with(vec![
-            Some(self.values.len() as f64),
+            Some(self.len() as f64),
             None::<f64>,
             None::<f64>,
             None::<f64>,
@@ -106,63 +281,23 @@ impl Col {
     }
 
     fn describe_numeric(&self) -> Dataframe {
-        if self.values.len() == 0 {
+        if self.is_empty() {
             return self.describe_with(vec![]);
         }
-        let mut total = self.typed.zero();
-        let mut sorted_set: Vec<Cell> = self
-            .values
-            .iter()
-            .filter(|cell| !cell.is_null())
-            .map(|cell| {
-                if let Some(c) = total.add_int(cell) {
-                    total = c;
-                }
-                cell.clone()
-            })
-            .collect();
-        sorted_set.sort_by(|cur, prev| match cur > prev {
-            true => Ordering::Greater,
-            false => Ordering::Less,
-        });
-        let min = &sorted_set[0];
-        let max = &sorted_set[sorted_set.len() - 1];
-        let mean = match total.div_float(sorted_set.len() as f64).unwrap() {
-            Cell::Float(val) => (val * 100.0).round() / 100.0,
-            _ => 0.0,
-        };
-        let sum_sqred_diffs: f64 = self
-            .values
-            .iter()
-            .map(|cell| {
-                (match cell {
-                    Cell::Int(val) => *val as f64,
-                    Cell::Uint(val) => *val as f64,
-                    Cell::Float(val) => *val,
-                    _ => 0.0,
-                } - mean)
-                    .powi(2)
-            })
-            .sum();
-        let std = match sorted_set.len() {
-            1 => None::<f64>,
-            _ => {
-                Some(((sum_sqred_diffs / self.values.len() as f64).sqrt() * 100.0).round() / 100.0)
-            }
-        };
+        let sorted_set = self.sorted_nums();
         let (quart, med, sev_fifth) = match quartiles(&sorted_set) {
             Some((quart, med, sev_fifth)) => (Some(quart), Some(med), Some(sev_fifth)),
             None => (None::<f64>, None::<f64>, None::<f64>),
         };
         self.describe_with(vec![
-            Some(self.values.len() as f64),
-            Some(mean),
-            std,
-            Some(min.to_float_val()),
+            Some(self.len() as f64),
+            self.mean(),
+            self.std().map(|std| (std * 100.0).round() / 100.0),
+            sorted_set.first().copied(),
             quart,
             med,
             sev_fifth,
-            Some(max.to_float_val()),
+            sorted_set.last().copied(),
             None::<f64>,
             None::<f64>,
             None::<f64>,
@@ -200,65 +335,49 @@ impl Col {
     }
 
     pub fn count(&self) -> usize {
-        self.values.len()
+        self.len()
     }
+    // Numeric stats skip nulls
     pub fn sum(&self) -> Option<f64> {
         match self.typed.is_num() {
-            true => Some(self.values.iter().map(|cell| cell.to_float_val()).sum()),
+            true => Some(self.nums().sum()),
             false => None,
         }
     }
     pub fn product(&self) -> Option<f64> {
         match self.typed.is_num() {
-            true => Some(self.values.iter().map(|cell| cell.to_float_val()).product()),
+            true => Some(self.nums().product()),
             false => None,
         }
     }
+    // None when there are no values to average
     pub fn mean(&self) -> Option<f64> {
-        match self.typed.is_num() {
-            true => Some(
-                (self
-                    .values
-                    .iter()
-                    .map(|cell| cell.to_float_val())
-                    .sum::<f64>()
-                    / self.values.len() as f64
-                    * 100.0)
-                    .round()
-                    / 100.0,
-            ),
-            false => None,
+        match (self.typed.is_num(), self.nums().count()) {
+            (false, _) | (_, 0) => None,
+            (true, len) => Some((self.nums().sum::<f64>() / len as f64 * 100.0).round() / 100.0),
         }
     }
     pub fn max(&self) -> Option<f64> {
         match self.typed.is_num() {
-            true => self
-                .values
-                .iter()
-                .map(|cell| cell.to_float_val())
-                .max_by(|x, y| x.total_cmp(y)),
+            true => self.nums().max_by(|x, y| x.total_cmp(y)),
             false => None,
         }
     }
     pub fn min(&self) -> Option<f64> {
         match self.typed.is_num() {
-            true => self
-                .values
-                .iter()
-                .map(|cell| cell.to_float_val())
-                .min_by(|x, y| x.total_cmp(y)),
+            true => self.nums().min_by(|x, y| x.total_cmp(y)),
             false => None,
         }
     }
     pub fn top(&self) -> Option<(Cell, f64, f64, f64)> {
-        if self.values.len() == 0 {
+        if self.is_empty() {
             return None;
         }
         let mut freq: HashMap<String, usize> = HashMap::new();
         let mut first_index: HashMap<String, usize> = HashMap::new();
         let mut most = 0;
-        let mut top = &self.typed.zero();
-        self.values.iter().enumerate().for_each(|(i, cell)| {
+        let mut top = self.typed.zero();
+        self.iter().enumerate().for_each(|(i, cell)| {
             let val = freq.entry(cell.as_string()).or_insert(0);
             *val += 1;
             first_index.entry(cell.as_string()).or_insert(i);
@@ -267,96 +386,259 @@ impl Col {
                 top = cell;
             }
         });
+        let top_index = *first_index.get(&top.as_string()).unwrap() as f64;
         Some((
-            top.clone(),                                        // top val
-            freq.len() as f64,                                  // unique
-            *first_index.get(&top.as_string()).unwrap() as f64, // top val idx
-            most as f64,                                        // top count
+            top,               // top val
+            freq.len() as f64, // unique
+            top_index,         // top val idx
+            most as f64,       // top count
         ))
     }
+    // Distinct non null values
     pub fn unique(&self) -> usize {
-        self.values
-            .iter()
+        self.non_nulls()
             .map(|cell| cell.as_string())
             .collect::<HashSet<String>>()
             .len()
     }
     pub fn coalesce(&self) -> Option<&Cell> {
-        self.values.iter().find(|cell| !cell.is_null())
+        (0..self.len())
+            .find(|i| !self.is_null(*i))
+            .and_then(|i| self.cell(i))
     }
     pub fn non_null(&self) -> usize {
-        self.values.iter().map(|cell| !cell.is_null()).count()
+        self.non_nulls().count()
+    }
+    fn non_nulls(&self) -> impl Iterator<Item = Cell> + '_ {
+        self.iter().filter(|cell| !cell.is_null())
+    }
+    fn nums(&self) -> impl Iterator<Item = f64> + '_ {
+        self.non_nulls().map(|cell| cell.to_float_val())
+    }
+
+    // Non null values of a numeric column in ascending order
+    fn sorted_nums(&self) -> Vec<f64> {
+        if !self.typed.is_num() {
+            return vec![];
+        }
+        let mut nums = self.nums().collect::<Vec<f64>>();
+        nums.sort_by(|x, y| x.total_cmp(y));
+        nums
+    }
+    // Linear interpolation between the closest ranks, `q` in 0..=1
+    pub fn quantile(&self, q: f64) -> Option<f64> {
+        let nums = self.sorted_nums();
+        if nums.is_empty() || !(0.0..=1.0).contains(&q) {
+            return None;
+        }
+        let pos = q * (nums.len() - 1) as f64;
+        let (lo, hi) = (nums[pos.floor() as usize], nums[pos.ceil() as usize]);
+        Some(lo + (hi - lo) * pos.fract())
+    }
+    pub fn median(&self) -> Option<f64> {
+        self.quantile(0.5)
+    }
+    // Population variance, none below two values
+    fn variance(&self) -> Option<f64> {
+        let nums = self.sorted_nums();
+        if nums.len() < 2 {
+            return None;
+        }
+        let mean = nums.iter().sum::<f64>() / nums.len() as f64;
+        Some(nums.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / nums.len() as f64)
+    }
+    pub fn var(&self) -> Option<f64> {
+        self.variance()
+    }
+    pub fn std(&self) -> Option<f64> {
+        self.variance().map(f64::sqrt)
+    }
+    pub fn first(&self) -> Option<Cell> {
+        self.get(0)
+    }
+    pub fn last(&self) -> Option<Cell> {
+        self.len().checked_sub(1).and_then(|i| self.get(i))
+    }
+    // Most frequent non null value, ties go to the first seen
+    pub fn mode(&self) -> Option<Cell> {
+        let mut freq: HashMap<Cell, usize> = HashMap::new();
+        let mut mode: Option<(Cell, usize)> = None;
+        for cell in self.non_nulls() {
+            let count = freq.entry(cell.clone()).or_insert(0);
+            *count += 1;
+            if mode.as_ref().is_none_or(|(_, most)| *count > *most) {
+                mode = Some((cell, *count));
+            }
+        }
+        mode.map(|(cell, _)| cell)
     }
 }
 
-fn median(sorted_set: &[Cell]) -> Option<f64> {
-    let med_idx = sorted_set.len() as f64 / 2.0;
-    let med = if med_idx.floor() == med_idx {
-        sorted_set
-            .get(med_idx as usize)?
-            .add_int(sorted_set.get(med_idx as usize - 1)?)?
-            .div_float(2.0)?
-    } else {
-        sorted_set.get(med_idx.floor() as usize)?.to_float()
-    };
-    Some(med.to_float_val())
+fn median(sorted_set: &[f64]) -> f64 {
+    let mid = sorted_set.len() / 2;
+    match sorted_set.len() % 2 {
+        0 => (sorted_set[mid - 1] + sorted_set[mid]) / 2.0,
+        _ => sorted_set[mid],
+    }
 }
-fn quartiles(sorted_set: &Vec<Cell>) -> Option<(f64, f64, f64)> {
+// Medians of the halves below and above the median, none below four values
+fn quartiles(sorted_set: &[f64]) -> Option<(f64, f64, f64)> {
     match sorted_set.len() {
         0..=3 => None,
-        _ => {
-            let med_idx = sorted_set.len() as f64 / 2.0;
-            let med = median(&sorted_set)?;
-            let (quart, sev_fifth) = (
-                median(&sorted_set[0..med_idx.floor() as usize])?,
-                median(&sorted_set[med_idx.ceil() as usize..sorted_set.len()])?,
-            );
-            Some((quart, med, sev_fifth))
-        }
+        len => Some((
+            median(&sorted_set[0..len / 2]),
+            median(sorted_set),
+            median(&sorted_set[len.div_ceil(2)..len]),
+        )),
     }
 }
 
-#[derive(Debug, PartialEq)]
+// Mutable `Vec<Cell>` view of a column from `Col::values_mut`
+pub struct ValuesMut<'a> {
+    col: &'a mut Col,
+    values: Vec<Cell>,
+}
+
+impl Deref for ValuesMut<'_> {
+    type Target = Vec<Cell>;
+    fn deref(&self) -> &Vec<Cell> {
+        &self.values
+    }
+}
+
+impl DerefMut for ValuesMut<'_> {
+    fn deref_mut(&mut self) -> &mut Vec<Cell> {
+        &mut self.values
+    }
+}
+
+impl Drop for ValuesMut<'_> {
+    fn drop(&mut self) {
+        let values = std::mem::take(&mut self.values);
+        *self.col = Col::build(self.col.name.clone(), values, self.col.typed.clone());
+    }
+}
+
+// Borrowed view of a column's rows, either a range of a `Col` or plain cells
+#[derive(Debug, Clone, Copy)]
 pub struct ColSlice<'a> {
     name: &'a str,
-    values: &'a [Cell],
     typed: &'a Cell,
+    rows: Rows<'a>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Rows<'a> {
+    Col {
+        col: &'a Col,
+        start: usize,
+        stop: usize,
+    },
+    Cells(&'a [Cell]),
+}
+
+impl PartialEq for ColSlice<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.name() == other.name()
+            && self.typed() == other.typed()
+            && self.len() == other.len()
+            && (0..self.len()).all(|i| self.at(i) == other.at(i))
+    }
 }
 
 impl<'a> ColSlice<'a> {
     pub fn new(name: &'a str, values: &'a [Cell], typed: &'a Cell) -> Self {
         ColSlice {
             name,
-            values,
             typed,
+            rows: Rows::Cells(values),
+        }
+    }
+    // Rows `start..stop` of `col`
+    pub(crate) fn range(col: &'a Col, start: usize, stop: usize) -> Self {
+        ColSlice {
+            name: col.name(),
+            typed: col.typed(),
+            rows: Rows::Col { col, start, stop },
+        }
+    }
+    pub fn len(&self) -> usize {
+        match self.rows {
+            Rows::Col { start, stop, .. } => stop - start,
+            Rows::Cells(values) => values.len(),
         }
     }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    pub fn get(&self, idx: usize) -> Option<Cell> {
+        match idx < self.len() {
+            true => Some(self.at(idx)),
+            false => None,
+        }
+    }
+    pub(crate) fn at(&self, idx: usize) -> Cell {
+        match self.rows {
+            Rows::Col { col, start, .. } => col.at(start + idx),
+            Rows::Cells(values) => values[idx].clone(),
+        }
+    }
+    pub fn iter(&self) -> impl Iterator<Item = Cell> + 'a {
+        let slice = *self;
+        (0..self.len()).map(move |i| slice.at(i))
+    }
     pub fn values(&self) -> &'a [Cell] {
-        &self.values
+        match self.rows {
+            Rows::Col { col, start, stop } => &col.values()[start..stop],
+            Rows::Cells(values) => values,
+        }
     }
-    pub fn name(&self) -> &str {
-        &self.name
+    // Borrowed cell at `idx` without building the whole column view
+    pub(crate) fn cell(&self, idx: usize) -> Option<&'a Cell> {
+        match self.rows {
+            Rows::Col { col, start, stop } if start + idx < stop => col.cell(start + idx),
+            Rows::Col { .. } => None,
+            Rows::Cells(values) => values.get(idx),
+        }
     }
-    pub fn typed(&self) -> &Cell {
-        &self.typed
+    pub fn slice(&self, start: usize, stop: usize) -> ColSlice<'a> {
+        match self.rows {
+            Rows::Col {
+                col, start: from, ..
+            } => ColSlice::range(col, from + start, from + stop),
+            Rows::Cells(values) => ColSlice::new(self.name, &values[start..stop], self.typed),
+        }
     }
-    pub fn empty_from(&self) -> Col {
-        Col {
-            name: self.name.to_string(),
-            values: vec![],
-            typed: self.typed.clone(),
-            type_check: cell_to_type_check(&self.typed),
+    // Rows at `indices` relative to the slice start
+    pub fn gather(&self, indices: &[usize]) -> Col {
+        match self.rows {
+            Rows::Col { col, start, .. } => {
+                col.gather(&indices.iter().map(|i| start + i).collect::<Vec<usize>>())
+            }
+            Rows::Cells(values) => Col::build(
+                self.name.to_string(),
+                indices.iter().map(|i| values[*i].clone()).collect(),
+                self.typed.clone(),
+            ),
         }
     }
+    pub fn to_col(&self) -> Col {
+        self.gather(&(0..self.len()).collect::<Vec<usize>>())
+    }
+    pub fn name(&self) -> &'a str {
+        self.name
+    }
+    pub fn typed(&self) -> &'a Cell {
+        self.typed
+    }
+    pub fn empty_from(&self) -> Col {
+        Col::build(self.name.to_string(), vec![], self.typed.clone())
+    }
 }
 
 impl<'a> From<&'a Col> for ColSlice<'a> {
     fn from(col: &'a Col) -> Self {
-        ColSlice {
-            name: &col.name,
-            values: &col.values[..],
-            typed: &col.typed,
-        }
+        ColSlice::range(col, 0, col.len())
     }
 }
 
@@ -0,0 +1,281 @@
+use crate::{cell::*, column::Col, util::Error};
+use chrono::{NaiveDate, NaiveDateTime};
+use csv::{Reader, ReaderBuilder, StringRecord, WriterBuilder};
+use std::{
+    collections::{HashMap, HashSet},
+    io::Read,
+};
+
+const DATE_TIME_FORMATS: [&str; 4] = [
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%d %H:%M:%S%.fZ",
+    "%Y-%m-%dT%H:%M:%S%.fZ",
+];
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+#[derive(Debug, Clone)]
+pub struct CsvOptions {
+    infer_rows: usize,
+    types: HashMap<String, Cell>,
+    delimiter: u8,
+    quote: u8,
+    has_header: bool,
+    null_token: String,
+    datetime_format: Option<String>,
+    float_precision: Option<usize>,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        CsvOptions {
+            infer_rows: 100,
+            types: HashMap::new(),
+            delimiter: b',',
+            quote: b'"',
+            has_header: true,
+            null_token: String::new(),
+            datetime_format: None,
+            float_precision: None,
+        }
+    }
+}
+
+impl CsvOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn infer_rows(mut self, rows: usize) -> Self {
+        self.infer_rows = rows;
+        self
+    }
+    // Skip inference for `col` and parse it as the type of `typed`, e.g. `Cell::Uint(0)`
+    pub fn col_type(mut self, col: &str, typed: Cell) -> Self {
+        self.types.insert(col.to_string(), typed.zero());
+        self
+    }
+    pub fn delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+    pub fn quote(mut self, quote: u8) -> Self {
+        self.quote = quote;
+        self
+    }
+    pub fn has_header(mut self, has_header: bool) -> Self {
+        self.has_header = has_header;
+        self
+    }
+    pub fn null_token(mut self, token: &str) -> Self {
+        self.null_token = token.to_string();
+        self
+    }
+    // chrono strftime format, e.g. "%d/%m/%Y %H:%M"
+    pub fn datetime_format(mut self, format: &str) -> Self {
+        self.datetime_format = Some(format.to_string());
+        self
+    }
+    pub fn float_precision(mut self, precision: usize) -> Self {
+        self.float_precision = Some(precision);
+        self
+    }
+
+    pub(crate) fn sample_size(&self) -> usize {
+        self.infer_rows
+    }
+    pub(crate) fn has_header_row(&self) -> bool {
+        self.has_header
+    }
+    pub(crate) fn reader(&self) -> ReaderBuilder {
+        let mut builder = ReaderBuilder::new();
+        builder
+            .delimiter(self.delimiter)
+            .quote(self.quote)
+            .has_headers(self.has_header);
+        builder
+    }
+    pub(crate) fn writer(&self) -> WriterBuilder {
+        let mut builder = WriterBuilder::new();
+        builder.delimiter(self.delimiter).quote(self.quote);
+        builder
+    }
+    pub(crate) fn is_null(&self, field: &str) -> bool {
+        field == self.null_token
+    }
+}
+
+pub(crate) fn read_labels<R: Read>(
+    reader: &mut Reader<R>,
+    options: &CsvOptions,
+) -> Result<Vec<String>, Error> {
+    let headers = match reader.headers() {
+        Ok(headers) => headers,
+        Err(e) => return Err(Error::new(e.to_string())),
+    };
+    Ok(match options.has_header {
+        true => headers.iter().map(|h| h.to_string()).collect(),
+        false => (0..headers.len()).map(|i| i.to_string()).collect(),
+    })
+}
+
+pub(crate) fn format_cell(cell: &Cell, options: &CsvOptions) -> String {
+    match (cell, &options.datetime_format, options.float_precision) {
+        (Cell::Null(_), _, _) => options.null_token.clone(),
+        (Cell::DateTime(x), Some(fmt), _) => x.format(fmt).to_string(),
+        (Cell::Float(x), _, Some(precision)) => format!("{x:.precision$}"),
+        // Debug keeps the trailing `.0` so whole floats are read back as floats
+        (Cell::Float(x), _, None) => format!("{x:?}"),
+        _ => cell.as_string(),
+    }
+}
+
+fn parse_date_time(field: &str, options: &CsvOptions) -> Option<NaiveDateTime> {
+    if let Some(fmt) = &options.datetime_format {
+        return NaiveDateTime::parse_from_str(field, fmt).ok().or_else(|| {
+            NaiveDate::parse_from_str(field, fmt)
+                .ok()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+        });
+    }
+    DATE_TIME_FORMATS
+        .iter()
+        .find_map(|fmt| NaiveDateTime::parse_from_str(field, fmt).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(field, DATE_FORMAT)
+                .ok()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+        })
+}
+
+fn parse_bool(field: &str) -> Option<bool> {
+    if field.eq_ignore_ascii_case("true") {
+        Some(true)
+    } else if field.eq_ignore_ascii_case("false") {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+fn parse_float(field: &str) -> Option<f64> {
+    // Only the spellings `format_cell` writes, other "nan"/"inf" style words would turn text
+    // columns into floats
+    match field {
+        "NaN" => return Some(f64::NAN),
+        "inf" => return Some(f64::INFINITY),
+        "-inf" => return Some(f64::NEG_INFINITY),
+        _ => (),
+    }
+    if !field.chars().any(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    field.parse::<f64>().ok()
+}
+
+fn detect(field: &str, options: &CsvOptions) -> Cell {
+    if field.parse::<i64>().is_ok() {
+        Cell::Int(0)
+    } else if parse_float(field).is_some() {
+        Cell::Float(0.0)
+    } else if parse_bool(field).is_some() {
+        Cell::Bool(false)
+    } else if let Some(dt) = parse_date_time(field, options) {
+        Cell::DateTime(dt).zero()
+    } else {
+        Cell::Str(String::new())
+    }
+}
+
+fn unify(current: Option<Cell>, next: Cell) -> Cell {
+    match current {
+        None => next,
+        Some(cur) if cur == next => cur,
+        Some(Cell::Int(_)) if next == Cell::Float(0.0) => next,
+        Some(Cell::Float(_)) if next == Cell::Int(0) => Cell::Float(0.0),
+        _ => Cell::Str(String::new()),
+    }
+}
+
+// Typed zero cell per column sampled from the first `infer_rows` records, columns
+// without values in the sample fall back to `Str`
+pub(crate) fn infer_schema(
+    labels: &[String],
+    records: &[StringRecord],
+    options: &CsvOptions,
+) -> Vec<Cell> {
+    labels
+        .iter()
+        .enumerate()
+        .map(|(i, label)| {
+            if let Some(typed) = options.types.get(label) {
+                return typed.clone();
+            }
+            records
+                .iter()
+                .take(options.infer_rows)
+                .filter_map(|record| record.get(i))
+                .filter(|field| !field.is_empty() && !options.is_null(field))
+                .fold(None, |typed, field| {
+                    Some(unify(typed, detect(field, options)))
+                })
+                .unwrap_or(Cell::Str(String::new()))
+        })
+        .collect()
+}
+
+// Empty fields are null for every type other than `Str`, where they are only null if
+// they match the null token
+pub(crate) fn parse_cell(field: &str, typed: &Cell, options: &CsvOptions) -> Option<Cell> {
+    if options.is_null(field) || (field.is_empty() && !cell_is_str(typed)) {
+        return Some(typed.null());
+    }
+    match typed {
+        Cell::Int(_) => field.parse::<i64>().ok().map(Cell::Int),
+        Cell::Uint(_) => field.parse::<u64>().ok().map(Cell::Uint),
+        Cell::Float(_) => parse_float(field).map(Cell::Float),
+        Cell::Bool(_) => parse_bool(field).map(Cell::Bool),
+        Cell::DateTime(_) => parse_date_time(field, options).map(Cell::DateTime),
+        Cell::Str(_) => Some(Cell::Str(field.to_string())),
+        Cell::Null(inner) => parse_cell(field, inner, options),
+    }
+}
+
+// `offset` is the data row index of the first record so errors point at the right row
+pub(crate) fn build_cols(
+    labels: &[String],
+    types: &[Cell],
+    records: &[StringRecord],
+    offset: usize,
+    options: &CsvOptions,
+) -> Result<Vec<Col>, Error> {
+    if labels.iter().collect::<HashSet<&String>>().len() != labels.len() {
+        return Err(Error::new("Col names must be unique".to_string()));
+    }
+    let mut values: Vec<Vec<Cell>> = labels
+        .iter()
+        .map(|_| Vec::with_capacity(records.len()))
+        .collect();
+    for (row, record) in records.iter().enumerate() {
+        if record.len() != labels.len() {
+            return Err(Error::new("Inconsistent data shape".to_string()));
+        }
+        for (i, field) in record.iter().enumerate() {
+            match parse_cell(field, &types[i], options) {
+                Some(cell) => values[i].push(cell),
+                None => {
+                    return Err(Error::new(format!(
+                        "Could not parse `{field}` as {} in column `{}` row {}",
+                        types[i].type_string(),
+                        labels[i],
+                        offset + row
+                    )))
+                }
+            }
+        }
+    }
+    Ok(values
+        .into_iter()
+        .enumerate()
+        .map(|(i, values)| Col::build(labels[i].clone(), values, types[i].clone()))
+        .collect())
+}
@@ -1,34 +1,61 @@
+#[cfg(feature = "arrow")]
+use crate::arrow_io;
+#[cfg(feature = "parquet")]
+use crate::parquet_io;
 pub use crate::{
     cell::*,
     column::*,
+    csv_io::CsvOptions,
     expression::{Op::*, *},
     group::Reducer::*,
+    join::{AsofDirection, AsofOptions, JoinOptions, JoinType},
+    json_io::JsonOrient,
     row,
     row::*,
     sort::{SortOrder::*, *},
     util::Error,
 };
 use crate::{
+    csv_io,
     dataslice::*,
-    group::DataGroup,
+    group::{DataGroup, Reducer},
     iterrows::{self, *},
+    join, json_io,
 };
-use csv::Writer;
-pub use dataframe_macros::ToRow;
-use serde::Deserialize;
+#[cfg(feature = "arrow")]
+use arrow_array::RecordBatch;
+use csv::StringRecord;
+pub use dataframe_macros::{FromRow, ToRow};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::{
     cmp::{max, min, Ordering},
     collections::{HashMap, HashSet},
     error::Error as StdError,
     fs::File,
+    io::{Read, Write},
 };
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(try_from = "RawDataframe")]
 pub struct Dataframe {
     title: String,
     columns: Vec<Col>,
 }
 
+#[derive(Deserialize)]
+struct RawDataframe {
+    title: String,
+    columns: Vec<Col>,
+}
+
+impl TryFrom<RawDataframe> for Dataframe {
+    type Error = Error;
+    fn try_from(raw: RawDataframe) -> Result<Self, Self::Error> {
+        Dataframe::new(Some(&raw.title)).set_columns(raw.columns)
+    }
+}
+
 impl Dataframe {
     pub fn new(title: Option<&str>) -> Self {
         Dataframe {
@@ -84,6 +111,25 @@ impl Dataframe {
         Ok(df)
     }
 
+    pub fn to_structs<T>(&self) -> Result<Vec<T>, Error>
+    where
+        T: FromRow,
+    {
+        let mut structs = Vec::with_capacity(self.length());
+        for i in 0..self.length() {
+            let row = self
+                .columns
+                .iter()
+                .map(|col| (col.name().to_string(), col.at(i)))
+                .collect::<HashMap<String, Cell>>();
+            match T::from_row(&row) {
+                Ok(val) => structs.push(val),
+                Err(e) => return Err(Error::new(format!("Row {i}: {e}"))),
+            }
+        }
+        Ok(structs)
+    }
+
     pub fn from_string_rows(labels: Vec<String>, rows: Vec<Vec<Cell>>) -> Result<Self, Error> {
         Self::do_from_rows(labels, rows)
     }
@@ -143,16 +189,46 @@ impl Dataframe {
     pub fn from_csv<T>(file_path: &str) -> Result<Self, Error>
     where
         for<'a> T: ToRow + Deserialize<'a>,
+    {
+        Self::from_csv_with::<T>(file_path, &CsvOptions::new())
+    }
+
+    pub fn from_csv_with<T>(file_path: &str, options: &CsvOptions) -> Result<Self, Error>
+    where
+        for<'a> T: ToRow + Deserialize<'a>,
+    {
+        match File::open(file_path) {
+            Ok(f) => Self::from_csv_reader::<T, File>(f, options),
+            Err(e) => Err(Error::new(e.to_string())),
+        }
+    }
+
+    pub fn from_csv_reader<T, R>(reader: R, options: &CsvOptions) -> Result<Self, Error>
+    where
+        for<'a> T: ToRow + Deserialize<'a>,
+        R: Read,
     {
         let mut rows: Vec<Vec<Cell>> = Vec::new();
         let mut labels = vec![];
-        let file = match File::open(file_path) {
-            Ok(f) => f,
-            Err(e) => return Err(Error::new(e.to_string())),
+        let mut reader = options.reader().from_reader(reader);
+        let headers = match options.has_header_row() {
+            true => match reader.headers() {
+                Ok(headers) => Some(headers.clone()),
+                Err(e) => return Err(Error::new(e.to_string())),
+            },
+            false => None,
         };
-        let mut reader = csv::Reader::from_reader(file);
-        for record in reader.deserialize() {
-            let record: T = match record {
+        for record in reader.records() {
+            let record = match record {
+                Ok(r) => r,
+                Err(e) => return Err(Error::new(e.to_string())),
+            };
+            // Blank out null tokens so they deserialize into `None`
+            let record = record
+                .iter()
+                .map(|field| if options.is_null(field) { "" } else { field })
+                .collect::<StringRecord>();
+            let record: T = match record.deserialize(headers.as_ref()) {
                 Ok(r) => r,
                 Err(e) => return Err(Error::new(e.to_string())),
             };
@@ -164,25 +240,168 @@ impl Dataframe {
         Self::from_rows(labels.iter().map(|l| l.as_str()).collect(), rows)
     }
 
+    pub fn read_csv(file_path: &str, options: &CsvOptions) -> Result<Self, Error> {
+        match File::open(file_path) {
+            Ok(f) => Self::read_csv_reader(f, options),
+            Err(e) => Err(Error::new(e.to_string())),
+        }
+    }
+
+    pub fn read_csv_reader<R: Read>(reader: R, options: &CsvOptions) -> Result<Self, Error> {
+        let mut reader = options.reader().from_reader(reader);
+        let labels = csv_io::read_labels(&mut reader, options)?;
+        let records = match reader.records().collect::<Result<Vec<StringRecord>, _>>() {
+            Ok(records) => records,
+            Err(e) => return Err(Error::new(e.to_string())),
+        };
+        let types = csv_io::infer_schema(&labels, &records, options);
+        Self::new(None).set_columns(csv_io::build_cols(&labels, &types, &records, 0, options)?)
+    }
+
+    pub fn scan_csv(file_path: &str, chunk_rows: usize) -> Result<ScanCsv<File>, Error> {
+        Self::scan_csv_with(file_path, chunk_rows, &CsvOptions::new())
+    }
+
+    pub fn scan_csv_with(
+        file_path: &str,
+        chunk_rows: usize,
+        options: &CsvOptions,
+    ) -> Result<ScanCsv<File>, Error> {
+        match File::open(file_path) {
+            Ok(f) => ScanCsv::new(f, chunk_rows, options),
+            Err(e) => Err(Error::new(e.to_string())),
+        }
+    }
+
+    pub fn scan_csv_reader<R: Read>(
+        reader: R,
+        chunk_rows: usize,
+        options: &CsvOptions,
+    ) -> Result<ScanCsv<R>, Error> {
+        ScanCsv::new(reader, chunk_rows, options)
+    }
+
     pub fn to_csv(&self, file_path: &str) -> Result<(), Box<dyn StdError>> {
-        let mut wtr = Writer::from_path(file_path)?;
-        wtr.write_record(
-            self.columns
-                .iter()
-                .map(|col| col.name().to_string())
-                .collect::<Vec<String>>(),
-        )?;
-        for i in 0..self.length() {
-            wtr.write_record(
-                self.columns
-                    .iter()
-                    .map(|col| col.values()[i].as_string())
-                    .collect::<Vec<String>>(),
-            )?;
+        self.to_csv_with(file_path, &CsvOptions::new())
+    }
+
+    pub fn to_csv_with(
+        &self,
+        file_path: &str,
+        options: &CsvOptions,
+    ) -> Result<(), Box<dyn StdError>> {
+        self.write_csv(File::create(file_path)?, options)
+    }
+
+    pub fn write_csv<W: Write>(
+        &self,
+        writer: W,
+        options: &CsvOptions,
+    ) -> Result<(), Box<dyn StdError>> {
+        self.to_slice().write_csv(writer, options)
+    }
+
+    pub fn to_json(&self, orient: JsonOrient) -> String {
+        match orient {
+            JsonOrient::Records => Value::Array(
+                (0..self.length())
+                    .map(|i| json_io::record(&self.columns, i))
+                    .collect(),
+            ),
+            JsonOrient::Columns => json_io::columns(&self.columns),
         }
-        wtr.flush()?;
-        Ok(())
+        .to_string()
+    }
+
+    pub fn to_ndjson(&self) -> String {
+        (0..self.length())
+            .map(|i| format!("{}\n", json_io::record(&self.columns, i)))
+            .collect()
+    }
+
+    // Records or columns orientation is detected from the top level JSON value
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        let cols = match serde_json::from_str::<Value>(json) {
+            Ok(Value::Array(records)) => json_io::records_to_cols(&records)?,
+            Ok(Value::Object(columns)) => json_io::columns_to_cols(&columns)?,
+            Ok(_) => return Err(Error::new("Invalid JSON dataframe".to_string())),
+            Err(e) => return Err(Error::new(e.to_string())),
+        };
+        Self::new(None).set_columns(cols)
+    }
+
+    pub fn from_ndjson(ndjson: &str) -> Result<Self, Error> {
+        let mut records = vec![];
+        for (i, line) in (1..).zip(ndjson.lines()) {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<Value>(line) {
+                Ok(record) => records.push(record),
+                Err(e) => return Err(Error::new(format!("Invalid JSON on line {i}: {e}"))),
+            }
+        }
+        Self::new(None).set_columns(json_io::records_to_cols(&records)?)
+    }
+
+    #[cfg(feature = "parquet")]
+    pub fn read_parquet(file_path: &str) -> Result<Self, Error> {
+        match File::open(file_path) {
+            Ok(f) => Self::new(None).set_columns(parquet_io::read(f, None)?),
+            Err(e) => Err(Error::new(e.to_string())),
+        }
+    }
+
+    // Only the columns in `cols` are decoded, in file order
+    #[cfg(feature = "parquet")]
+    pub fn read_parquet_cols(file_path: &str, cols: HashSet<&str>) -> Result<Self, Error> {
+        match File::open(file_path) {
+            Ok(f) => Self::new(None).set_columns(parquet_io::read(f, Some(cols))?),
+            Err(e) => Err(Error::new(e.to_string())),
+        }
+    }
+
+    #[cfg(feature = "parquet")]
+    pub fn write_parquet(&self, file_path: &str) -> Result<(), Error> {
+        match File::create(file_path) {
+            Ok(f) => parquet_io::write(f, &self.columns),
+            Err(e) => Err(Error::new(e.to_string())),
+        }
+    }
+
+    #[cfg(feature = "arrow")]
+    pub fn to_record_batch(&self) -> Result<RecordBatch, Error> {
+        arrow_io::to_record_batch(&self.columns, self.length())
     }
+
+    #[cfg(feature = "arrow")]
+    pub fn from_record_batch(batch: &RecordBatch) -> Result<Self, Error> {
+        Self::new(None).set_columns(arrow_io::from_record_batch(batch)?)
+    }
+
+    // Arrow IPC file format, AKA Feather v2
+    #[cfg(feature = "arrow")]
+    pub fn write_ipc(&self, file_path: &str) -> Result<(), Error> {
+        let batch = self.to_record_batch()?;
+        match File::create(file_path) {
+            Ok(f) => arrow_io::write_ipc(f, &batch),
+            Err(e) => Err(Error::new(e.to_string())),
+        }
+    }
+
+    #[cfg(feature = "arrow")]
+    pub fn read_ipc(file_path: &str) -> Result<Self, Error> {
+        let (schema, batches) = match File::open(file_path) {
+            Ok(f) => arrow_io::read_ipc(f)?,
+            Err(e) => return Err(Error::new(e.to_string())),
+        };
+        let mut df = Self::from_record_batch(&RecordBatch::new_empty(schema))?;
+        for batch in batches {
+            df.concat(Self::from_record_batch(&batch)?)?;
+        }
+        Ok(df)
+    }
+
     pub fn rename_col(&mut self, from: &str, to: &str) -> Result<(), Error> {
         match self.columns.iter_mut().find(|col| col.name() == from) {
             Some(col) => {
@@ -200,7 +419,7 @@ impl Dataframe {
             .collect()
     }
 
-    pub fn col_map_mut(&mut self) -> HashMap<String, &mut Vec<Cell>> {
+    pub fn col_map_mut(&mut self) -> HashMap<String, ValuesMut<'_>> {
         self.columns
             .iter_mut()
             .map(|c| (c.name().to_string(), c.values_mut()))
@@ -221,10 +440,10 @@ impl Dataframe {
             .collect()
     }
 
-    fn set_columns(mut self, cols: Vec<Col>) -> Result<Self, Error> {
+    pub(crate) fn set_columns(mut self, cols: Vec<Col>) -> Result<Self, Error> {
         if cols.len() > 0 {
-            let l = cols[0].values().len();
-            match cols.iter().find(|c| c.values().len() != l) {
+            let l = cols[0].len();
+            match cols.iter().find(|c| c.len() != l) {
                 Some(_) => return Err(Error::new("Inconsistent data shape".to_string())),
                 None => (),
             };
@@ -270,43 +489,45 @@ impl Dataframe {
             return Err(Error::new("Invalid row length".to_string()));
         }
         for (i, col) in self.columns.iter().enumerate() {
-            if col.values().len() > 0 && col.values()[0].zero() != row[i].zero() {
+            if !col.is_empty() && col.typed().zero() != row[i].zero() {
                 return Err(Error::new("Invalid col types".to_string()));
             }
         }
-        row.into_iter().enumerate().for_each(|(i, cell)| {
-            self.columns[i].values_mut().push(cell);
-        });
+        for (i, cell) in row.into_iter().enumerate() {
+            let col = &mut self.columns[i];
+            // Empty columns take the type of their first row
+            if col.is_empty() && !col.check_type(&cell) {
+                *col = Col::build(col.name().to_string(), vec![], cell.zero());
+            }
+            col.push(cell)?;
+        }
         Ok(())
     }
 
     pub fn filter(self, exp: Exp) -> Result<Self, Error> {
-        let col_map = self.col_map();
-        let filter_set = (0..self.length())
-            .map(|i| {
-                let val_map: HashMap<String, &Cell> =
-                    col_map.iter().map(|(k, v)| (k.to_owned(), &v[i])).collect();
+        let indices = (0..self.length())
+            .filter(|i| {
+                let cells = self
+                    .columns
+                    .iter()
+                    .map(|col| col.at(*i))
+                    .collect::<Vec<Cell>>();
+                let val_map: HashMap<String, &Cell> = self
+                    .columns
+                    .iter()
+                    .zip(cells.iter())
+                    .map(|(col, cell)| (col.name().to_string(), cell))
+                    .collect();
                 exp.evaluate(&val_map)
             })
-            .collect::<Vec<bool>>();
+            .collect::<Vec<usize>>();
 
         Ok(Dataframe {
             title: self.title.clone(),
             columns: self
                 .columns
                 .iter()
-                .map(|col| {
-                    Col::build(
-                        col.name().to_string(),
-                        col.values()
-                            .iter()
-                            .enumerate()
-                            .filter(|(i, _)| filter_set[*i])
-                            .map(|(_, c)| c.clone())
-                            .collect(),
-                        col.typed().clone(),
-                    )
-                })
+                .map(|col| col.gather(&indices))
                 .collect(),
         })
     }
@@ -323,7 +544,7 @@ impl Dataframe {
             &self.title,
             self.columns
                 .iter()
-                .map(|col| ColSlice::new(&col.name(), &col.values()[start..stop], &col.typed()))
+                .map(|col| ColSlice::range(col, start, stop))
                 .collect(),
         ))
     }
@@ -334,7 +555,7 @@ impl Dataframe {
             self.columns
                 .iter()
                 .filter(|col| cols.contains(col.name()))
-                .map(|col| ColSlice::new(&col.name(), &col.values(), &col.typed()))
+                .map(|col| col.into())
                 .collect(),
         ))
     }
@@ -370,123 +591,145 @@ impl Dataframe {
     }
 
     pub fn join(&self, with: &Dataframe, left: &str, right: &str) -> Result<Self, Error> {
-        self.do_join(with, (left, right), false)
+        self.join_with(with, &[(left, right)], JoinType::Inner)
     }
     pub fn left_join(&self, with: &Dataframe, left: &str, right: &str) -> Result<Self, Error> {
-        self.do_join(with, (left, right), true)
+        self.join_with(with, &[(left, right)], JoinType::Left)
     }
-    fn do_join(&self, with: &Dataframe, on: (&str, &str), left: bool) -> Result<Self, Error> {
-        let self_index = self.column(on.0)?;
-        let with_index = with.column(on.1)?;
-        let match_count = self.match_count(&with);
-        if (on.0 == on.1 && match_count != 1) || (on.0 != on.1 && match_count != 0) {
-            return Err(Error::new(
-                "Join dataframe columns are not unique".to_string(),
-            ));
+    // `on` lists (left, right) key column pairs, it is ignored by cross joins which keep
+    // every column of both sides
+    pub fn join_with(
+        &self,
+        with: &Dataframe,
+        on: &[(&str, &str)],
+        how: JoinType,
+    ) -> Result<Self, Error> {
+        self.join_with_options(with, on, how, &JoinOptions::new())
+    }
+    pub fn join_with_options(
+        &self,
+        with: &Dataframe,
+        on: &[(&str, &str)],
+        how: JoinType,
+        options: &JoinOptions,
+    ) -> Result<Self, Error> {
+        if on.is_empty() && how != JoinType::Cross {
+            return Err(Error::new("Join requires at least one key".to_string()));
         }
-        let mut intersect_map: HashMap<String, Vec<usize>> = HashMap::new();
-        with_index.values().iter().enumerate().for_each(|(i, val)| {
-            intersect_map
-                .entry(val.as_string())
-                .or_insert(vec![])
-                .push(i);
-        });
-        // To prevent adding index twice
-        let with_slice = with.col_slice(
-            with.columns
-                .iter()
-                .map(|col| col.name())
-                .filter(|name| *name != on.1)
-                .collect(),
-        )?;
-        let mut new_df = Dataframe {
-            title: self.title.clone(),
-            columns: vec![
-                self.columns
-                    .iter()
-                    .map(|c| c.empty_from())
-                    .collect::<Vec<Col>>(),
-                with_slice
-                    .columns()
-                    .iter()
-                    .map(|c| c.empty_from())
-                    .collect::<Vec<Col>>(),
-            ]
-            .into_iter()
-            .flatten()
-            .collect::<Vec<Col>>(),
-        };
-        self_index.values().iter().enumerate().for_each(|(i, val)| {
-            if let Some(indices) = intersect_map.get_mut(&val.as_string()) {
-                indices.iter().for_each(|with_i| {
-                    self.columns.iter().enumerate().for_each(|(col_i, col)| {
-                        new_df.columns[col_i]
-                            .values_mut()
-                            .push(col.values()[i].clone())
-                    });
-                    with_slice
-                        .columns()
-                        .iter()
-                        .enumerate()
-                        .for_each(|(col_j, col)| {
-                            new_df.columns[self.columns.len() + col_j]
-                                .values_mut()
-                                .push(col.values()[*with_i].clone())
-                        });
-                })
-            } else if left {
-                self.columns.iter().enumerate().for_each(|(col_i, col)| {
-                    new_df.columns[col_i]
-                        .values_mut()
-                        .push(col.values()[i].clone())
-                });
-                with_slice
-                    .columns()
+        let self_keys = on
+            .iter()
+            .map(|(left, _)| self.column(left))
+            .collect::<Result<Vec<&Col>, Error>>()?;
+        let with_keys = on
+            .iter()
+            .map(|(_, right)| with.column(right))
+            .collect::<Result<Vec<&Col>, Error>>()?;
+        let (left_idx, right_idx) = join::join_indices(
+            (&self_keys, self.length()),
+            (&with_keys, with.length()),
+            how,
+            options.nulls_match(),
+        );
+        let mut columns = self
+            .columns
+            .iter()
+            .map(|col| col.gather_opt(&left_idx))
+            .collect::<Vec<Col>>();
+        if let JoinType::Semi | JoinType::Anti = how {
+            return Ok(Dataframe {
+                title: self.title.clone(),
+                columns,
+            });
+        }
+        let merge_keys = how != JoinType::Cross && !options.keeps_keys();
+        if merge_keys {
+            // Rows only found on the right take their keys from the right index, keys
+            // that don't fit the left type widen the column
+            for ((left, _), with_key) in on.iter().zip(&with_keys) {
+                let key = columns.iter_mut().find(|col| col.name() == *left).unwrap();
+                let cells = left_idx
                     .iter()
+                    .zip(&right_idx)
                     .enumerate()
-                    .for_each(|(col_j, col)| {
-                        new_df.columns[self.columns.len() + col_j]
-                            .values_mut()
-                            .push(col.typed().null())
-                    });
+                    .map(|(row, (i, j))| match (i, j) {
+                        (None, Some(j)) => join::cast_key(with_key.at(*j), key.typed()),
+                        _ => key.at(row),
+                    })
+                    .collect();
+                *key = Col::build_unified(key.name().to_string(), cells, key.typed().clone());
             }
-        });
-        Ok(new_df)
+        }
+        let with_columns = with
+            .columns
+            .iter()
+            // To prevent adding index twice
+            .filter(|col| !merge_keys || !on.iter().any(|(_, right)| col.name() == *right))
+            .map(|col| col.gather_opt(&right_idx))
+            .collect::<Vec<Col>>();
+        Ok(Dataframe {
+            title: self.title.clone(),
+            columns: join::merge_columns(columns, with_columns, options)?,
+        })
+    }
+
+    // Left join on the closest key, see `AsofDirection`
+    pub fn join_asof(
+        &self,
+        with: &Dataframe,
+        left_on: &str,
+        right_on: &str,
+        direction: AsofDirection,
+    ) -> Result<Self, Error> {
+        self.join_asof_with(with, left_on, right_on, direction, &AsofOptions::new())
+    }
+    pub fn join_asof_with(
+        &self,
+        with: &Dataframe,
+        left_on: &str,
+        right_on: &str,
+        direction: AsofDirection,
+        options: &AsofOptions,
+    ) -> Result<Self, Error> {
+        let by = match options.by_cols() {
+            Some((left, right)) => Some((self.column(left)?, with.column(right)?)),
+            None => None,
+        };
+        let right_idx = join::asof_indices(
+            self.column(left_on)?,
+            with.column(right_on)?,
+            by,
+            direction,
+            options,
+        )?;
+        let with_columns = with
+            .columns
+            .iter()
+            // The by column repeats the left one, as does a key of the same name
+            .filter(|col| {
+                options
+                    .by_cols()
+                    .is_none_or(|(_, right)| col.name() != right)
+                    && (left_on != right_on || col.name() != right_on)
+            })
+            .map(|col| col.gather_opt(&right_idx))
+            .collect::<Vec<Col>>();
+        Ok(Dataframe {
+            title: self.title.clone(),
+            columns: join::merge_columns(self.columns.clone(), with_columns, &JoinOptions::new())?,
+        })
     }
 
     pub fn sort(&mut self, by: &str, order: SortOrder) -> Result<(), Error> {
-        let self_index = self.col_mut(&by)?;
-        let mut sort_instructions = Vec::new();
-        self_index.values_mut().sort_by(|cur, prev| match order {
-            SortOrder::Asc => {
-                if cur > prev {
-                    sort_instructions.push(Ordering::Greater);
-                    Ordering::Greater
-                } else {
-                    sort_instructions.push(Ordering::Less);
-                    Ordering::Less
-                }
-            }
-            SortOrder::Desc => {
-                if cur > prev {
-                    sort_instructions.push(Ordering::Less);
-                    Ordering::Less
-                } else {
-                    sort_instructions.push(Ordering::Greater);
-                    Ordering::Greater
-                }
-            }
-        });
-        self.col_map_mut().iter_mut().for_each(|(name, col)| {
-            let mut idx = 0;
-            if name != by {
-                col.sort_by(|_, _| {
-                    let ord = sort_instructions[idx];
-                    idx += 1;
-                    ord
-                })
+        let self_index = self.column(by)?;
+        let mut perm = (0..self_index.len()).collect::<Vec<usize>>();
+        perm.sort_by(|cur, prev| {
+            let greater = self_index.at(*cur) > self_index.at(*prev);
+            match (&order, greater) {
+                (SortOrder::Asc, true) | (SortOrder::Desc, false) => Ordering::Greater,
+                _ => Ordering::Less,
             }
         });
+        self.columns = self.columns.iter().map(|col| col.gather(&perm)).collect();
         Ok(())
     }
 
@@ -521,25 +764,15 @@ impl Dataframe {
     }
 
     pub fn cell(&self, idx: usize, col: &str) -> Option<&Cell> {
-        if let Ok(col) = self.column(col) {
-            if idx < col.values().len() {
-                return Some(&col.values()[idx]);
-            }
+        match self.column(col) {
+            Ok(col) => col.cell(idx),
+            Err(_) => None,
         }
-        None
     }
 
     pub fn set_val<T: ToCell>(&mut self, idx: usize, col_name: &str, val: T) -> Result<(), Error> {
         if let Ok(col) = self.col_mut(col_name) {
-            if idx >= col.values().len() {
-                return Err(Error::new("Index out of bounds".to_string()));
-            }
-            let new_cell = val.to_cell();
-            if !col.check_type(&new_cell) {
-                return Err(Error::new("Invalid cell type".to_string()));
-            }
-            col.values_mut()[idx] = new_cell;
-            Ok(())
+            col.set(idx, val.to_cell())
         } else {
             Err(Error::new("Column not found".to_string()))
         }
@@ -553,17 +786,12 @@ impl Dataframe {
     ) -> Result<(), Error> {
         match self.columns.iter_mut().find(|col| col.name() == col_name) {
             Some(col) => {
-                if idx >= col.values().len() {
-                    return Err(Error::new("Index out of bounds".to_string()));
-                }
-                let mut new_cell = col.values()[idx].clone();
-                f(&mut new_cell);
-                if col.check_type(&new_cell) {
-                    col.values_mut()[idx] = new_cell;
-                } else {
-                    return Err(Error::new("Invalid cell type".to_string()));
+                let mut new_cell = match col.get(idx) {
+                    Some(cell) => cell,
+                    None => return Err(Error::new("Index out of bounds".to_string())),
                 };
-                Ok(())
+                f(&mut new_cell);
+                col.set(idx, new_cell)
             }
             None => Err(Error::new("Column not found".to_string())),
         }
@@ -575,12 +803,11 @@ impl Dataframe {
                 "Concat against mismatched dataframes".to_string(),
             ));
         }
-        let mut self_map = self.col_map_mut();
-        with.into_col_map().into_iter().for_each(|(name, ext_col)| {
-            if let Some(loc_col) = self_map.get_mut(&name) {
-                loc_col.extend(ext_col);
+        for loc_col in self.columns.iter_mut() {
+            if let Some(ext_col) = with.columns.iter().find(|c| c.name() == loc_col.name()) {
+                loc_col.extend(ext_col)?;
             }
-        });
+        }
         Ok(())
     }
 
@@ -588,7 +815,7 @@ impl Dataframe {
         if self.columns.len() == 0 {
             return 0;
         }
-        self.columns[0].values().len()
+        self.columns[0].len()
     }
 
     pub fn head(&self, count: usize) {
@@ -673,6 +900,184 @@ impl Dataframe {
     pub fn group_by(&self, by: &str) -> DataGroup {
         DataGroup::new(self.to_slice(), by.to_string())
     }
+    pub fn group_by_cols(&self, by: Vec<&str>) -> DataGroup<'_> {
+        DataGroup::by_cols(self.to_slice(), by.iter().map(|b| b.to_string()).collect())
+    }
+
+    // One row per `index` value and one column per `columns` value, holding `values` reduced
+    // over their rows. Missing combinations are typed nulls, both keep first-seen order.
+    pub fn pivot(
+        &self,
+        index: &str,
+        columns: &str,
+        values: &str,
+        reducer: Reducer,
+    ) -> Result<Self, Error> {
+        let index_col = self.column(index)?;
+        self.column(columns)?;
+        self.column(values)?;
+        // An alias apart from the keys, so `values` can also be `index` or `columns`
+        let mut alias = "__value".to_string();
+        while [index, columns, values].contains(&alias.as_str()) {
+            alias.push('_');
+        }
+        let reduced = self
+            .group_by_cols(vec![index, columns])
+            .select(values, reducer, &alias)
+            .to_dataframe()?;
+        if reduced.length() == 0 {
+            return Dataframe::new(Some(&self.title)).set_columns(vec![index_col.empty_from()]);
+        }
+        let (keys, headers, cells) = (
+            &reduced.columns[0],
+            &reduced.columns[1],
+            &reduced.columns[2],
+        );
+        // First reduced row of each index value, and the reduced row of each combination
+        let mut rows: HashMap<Cell, usize> = HashMap::new();
+        let mut row_starts = vec![];
+        let mut header_idx: HashMap<Cell, usize> = HashMap::new();
+        let mut grid: Vec<Vec<Option<usize>>> = vec![];
+        for i in 0..reduced.length() {
+            let row = *rows.entry(keys.at(i)).or_insert_with(|| {
+                row_starts.push(i);
+                row_starts.len() - 1
+            });
+            let header = *header_idx.entry(headers.at(i)).or_insert_with(|| {
+                grid.push(vec![]);
+                grid.len() - 1
+            });
+            grid[header].resize(row + 1, None);
+            grid[header][row] = Some(i);
+        }
+        let mut header_cells = header_idx.into_iter().collect::<Vec<(Cell, usize)>>();
+        header_cells.sort_by_key(|(_, idx)| *idx);
+        let mut pivoted = Dataframe::new(Some(&self.title));
+        pivoted.columns.push(keys.gather(&row_starts));
+        for ((header, _), mut slots) in header_cells.into_iter().zip(grid) {
+            // A null would name its column "Null" like the string does
+            if header.is_null() {
+                return Err(Error::new(format!(
+                    "Null header value in column `{columns}`"
+                )));
+            }
+            if pivoted
+                .columns
+                .iter()
+                .any(|c| c.name() == header.as_string())
+            {
+                return Err(Error::new("Col names must be unique".to_string()));
+            }
+            slots.resize(row_starts.len(), None);
+            let mut col = cells.gather_opt(&slots);
+            col.rename(header.as_string());
+            pivoted.columns.push(col);
+        }
+        Ok(pivoted)
+    }
+
+    // Rows become columns named by the `header_col` values, the first column holds the other
+    // column names. Mixed types in a new column are unified, see `unify_cells`.
+    pub fn transpose(&self, header_col: &str) -> Result<Self, Error> {
+        let header = self.column(header_col)?;
+        let others = self
+            .columns
+            .iter()
+            .filter(|col| col.name() != header_col)
+            .collect::<Vec<&Col>>();
+        let mut transposed = Dataframe::new(Some(&self.title));
+        transposed.columns.push(Col::build(
+            header_col.to_string(),
+            others
+                .iter()
+                .map(|col| Cell::Str(col.name().to_string()))
+                .collect(),
+            Cell::Str(String::new()),
+        ));
+        for i in 0..self.length() {
+            // A null would name its column "Null" like the string does
+            if header.is_null(i) {
+                return Err(Error::new(format!(
+                    "Null header value in column `{header_col}`"
+                )));
+            }
+            let name = header.at(i).as_string();
+            if transposed.columns.iter().any(|col| col.name() == name) {
+                return Err(Error::new("Col names must be unique".to_string()));
+            }
+            let (typed, cells) = unify_cells(others.iter().map(|col| col.at(i)).collect());
+            transposed.columns.push(Col::build(name, cells, typed));
+        }
+        Ok(transposed)
+    }
+
+    // Unpivots `value_vars` into `variable` and `value` columns, one row per input row and
+    // value column. All non id columns are melted when `value_vars` is empty and value
+    // columns of different types are unified, e.g. Int and Float become Float.
+    pub fn melt(&self, id_vars: Vec<&str>, value_vars: Vec<&str>) -> Result<Self, Error> {
+        let ids = id_vars
+            .iter()
+            .map(|name| self.column(name))
+            .collect::<Result<Vec<&Col>, Error>>()?;
+        let vars = match value_vars.is_empty() {
+            true => self
+                .columns
+                .iter()
+                .filter(|col| !id_vars.contains(&col.name()))
+                .collect(),
+            false => value_vars
+                .iter()
+                .map(|name| self.column(name))
+                .collect::<Result<Vec<&Col>, Error>>()?,
+        };
+        let len = self.length();
+        let repeat = (0..vars.len()).flat_map(|_| 0..len).collect::<Vec<usize>>();
+        let mut columns = ids
+            .iter()
+            .map(|col| col.gather(&repeat))
+            .collect::<Vec<Col>>();
+        columns.push(Col::new(
+            "variable".to_string(),
+            vars.iter()
+                .flat_map(|col| (0..len).map(|_| col.name()))
+                .collect::<Vec<&str>>(),
+        ));
+        let mut value = match vars.first() {
+            Some(col) => col.empty_from(),
+            None => {
+                return Err(Error::new(
+                    "Melt requires at least one value column".to_string(),
+                ))
+            }
+        };
+        match vars.iter().all(|col| col.typed() == value.typed()) {
+            true => {
+                for col in vars.iter() {
+                    value.extend(col)?;
+                }
+            }
+            // Mixed value types are unified, see `unify_cells`
+            false => {
+                value = Col::build_unified(
+                    String::new(),
+                    vars.iter().flat_map(|col| col.iter()).collect(),
+                    value.typed().clone(),
+                )
+            }
+        }
+        value.rename("value".to_string());
+        columns.push(value);
+        if columns
+            .iter()
+            .map(|col| col.name())
+            .collect::<HashSet<&str>>()
+            .len()
+            != columns.len()
+        {
+            return Err(Error::new("Col names must be unique".to_string()));
+        }
+        Dataframe::new(Some(&self.title)).set_columns(columns)
+    }
     pub fn iter_sql<'a, 'b>(&'a self, table_name: &'b str, size: usize) -> IterSQL<'a, 'b> {
         IterSQL::new(self.into(), table_name, size)
     }
@@ -682,76 +1087,59 @@ impl Dataframe {
 impl<'a> DataSlice<'a> {
     pub fn to_dataframe(&self) -> Dataframe {
         Dataframe::new(Some(self.title()))
-            .set_columns(
-                self.columns()
-                    .iter()
-                    .map(|col| {
-                        Col::build(
-                            col.name().to_string(),
-                            col.values().iter().map(|val| val.clone()).collect(),
-                            col.typed().clone(),
-                        )
-                    })
-                    .collect(),
-            )
+            .set_columns(self.columns().iter().map(|col| col.to_col()).collect())
             .unwrap()
     }
 
     pub fn chunk_by(&self, by: &str) -> Result<Vec<Dataframe>, Error> {
-        let mut chunks_idx: HashMap<String, usize> = HashMap::new();
-        let mut chunks: Vec<Vec<Vec<Cell>>> = vec![];
-        let by_idx = match self
-            .columns()
+        self.chunk_by_cols(&[by])
+    }
+
+    // One dataframe per distinct tuple of `by` values, in first-seen order
+    pub fn chunk_by_cols(&self, by: &[&str]) -> Result<Vec<Dataframe>, Error> {
+        Ok(self
+            .group_indices(by)?
             .iter()
-            .enumerate()
-            .find(|(_, c)| c.name() == by)
-            .map(|(i, _)| i)
-        {
-            Some(v) => v,
-            None => return Err(Error::new("Group by col not found".to_string())),
-        };
+            .map(|indices| self.take(indices))
+            .collect())
+    }
+
+    // Row indices of each distinct tuple of `by` values, in first-seen order
+    pub(crate) fn group_indices(&self, by: &[&str]) -> Result<Vec<Vec<usize>>, Error> {
+        let mut chunks_idx: HashMap<Vec<Cell>, usize> = HashMap::new();
+        let mut chunks: Vec<Vec<usize>> = vec![];
+        let by_cols = by
+            .iter()
+            .map(
+                |name| match self.columns().iter().find(|c| c.name() == *name) {
+                    Some(c) => Ok(c),
+                    None => Err(Error::new("Group by col not found".to_string())),
+                },
+            )
+            .collect::<Result<Vec<&ColSlice>, Error>>()?;
         (0..self.length()).for_each(|i| {
-            let key = self
-                .columns()
-                .get(by_idx)
-                .unwrap()
-                .values()
-                .get(i)
-                .unwrap()
-                .as_string();
+            let key = by_cols.iter().map(|col| col.at(i)).collect::<Vec<Cell>>();
             let chunk_idx = match chunks_idx.get(&key) {
                 Some(i) => *i,
                 None => {
-                    chunks.push(self.columns().iter().map(|_| vec![]).collect());
+                    chunks.push(vec![]);
                     chunks_idx.insert(key, chunks.len() - 1);
                     chunks.len() - 1
                 }
             };
-            let chunk = chunks.get_mut(chunk_idx).unwrap();
-            self.columns()
-                .iter()
-                .enumerate()
-                .for_each(|(j, col)| chunk.get_mut(j).unwrap().push(col.values()[i].clone()));
+            chunks[chunk_idx].push(i);
         });
-        Ok(chunks
-            .into_iter()
-            .map(|df_cols| {
-                Dataframe::new(None)
-                    .set_columns(
-                        df_cols
-                            .into_iter()
-                            .enumerate()
-                            .map(|(i, values)| {
-                                Col::build(
-                                    self.columns()[i].name().to_string(),
-                                    values,
-                                    self.columns()[i].typed().clone(),
-                                )
-                            })
-                            .collect(),
-                    )
-                    .unwrap()
-            })
-            .collect())
+        Ok(chunks)
+    }
+
+    pub(crate) fn take(&self, indices: &[usize]) -> Dataframe {
+        Dataframe::new(None)
+            .set_columns(
+                self.columns()
+                    .iter()
+                    .map(|col| col.gather(indices))
+                    .collect(),
+            )
+            .unwrap()
     }
 }
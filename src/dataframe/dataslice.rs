@@ -1,3 +1,4 @@
+use crate::csv_io;
 use crate::dataframe::*;
 use crate::format::*;
 use crate::iterrows;
@@ -5,6 +6,8 @@ use crate::iterrows::*;
 use crate::util::*;
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::error::Error as StdError;
+use std::io::Write;
 
 #[derive(Debug, PartialEq)]
 pub struct DataSlice<'a> {
@@ -27,7 +30,7 @@ impl<'a> DataSlice<'a> {
         if self.columns.len() == 0 {
             return 0;
         }
-        self.columns[0].values().len()
+        self.columns[0].len()
     }
     pub fn col_names(&self) -> Vec<&str> {
         self.columns().iter().map(|col| col.name()).collect()
@@ -44,7 +47,7 @@ impl<'a> DataSlice<'a> {
             columns: self
                 .columns
                 .iter()
-                .map(|col| ColSlice::new(&col.name(), &col.values()[start..stop], &col.typed()))
+                .map(|col| col.slice(start, stop))
                 .collect(),
         })
     }
@@ -55,7 +58,7 @@ impl<'a> DataSlice<'a> {
                 .columns
                 .iter()
                 .filter(|col| cols.contains(col.name()))
-                .map(|col| ColSlice::new(&col.name(), &col.values(), &col.typed()))
+                .copied()
                 .collect(),
         })
     }
@@ -82,6 +85,27 @@ impl<'a> DataSlice<'a> {
         iterrows::Iterrows::new(self)
     }
 
+    pub fn write_csv<W: Write>(
+        &self,
+        writer: W,
+        options: &CsvOptions,
+    ) -> Result<(), Box<dyn StdError>> {
+        let mut wtr = options.writer().from_writer(writer);
+        if options.has_header_row() {
+            wtr.write_record(self.col_names())?;
+        }
+        for i in 0..self.length() {
+            wtr.write_record(
+                self.columns
+                    .iter()
+                    .map(|col| csv_io::format_cell(&col.at(i), options))
+                    .collect::<Vec<String>>(),
+            )?;
+        }
+        wtr.flush()?;
+        Ok(())
+    }
+
     pub fn to_sql(&self, table_name: &str) -> (String, Vec<String>) {
         let cols = self
             .col_names()
@@ -95,12 +119,12 @@ impl<'a> DataSlice<'a> {
                 let vals = self
                     .columns
                     .iter()
-                    .map(|col| match col.values()[i] {
-                        Cell::Str(_) => {
-                            args.push(col.values()[i].as_string());
+                    .map(|col| match col.at(i) {
+                        Cell::Str(x) => {
+                            args.push(x);
                             "?".to_string()
                         }
-                        _ => col.values()[i].to_sql(),
+                        cell => cell.to_sql(),
                     })
                     .collect::<Vec<String>>()
                     .join(", ");
@@ -219,6 +219,16 @@ impl Exp {
             Self::Not(ex) => !ex.evaluate(against),
         }
     }
+    // Column names the expression reads
+    pub(crate) fn targets(&self) -> Vec<&str> {
+        match self {
+            Self::ExpU(ex) => vec![ex.target.as_str()],
+            Self::Or(Or { vexp }) | Self::And(And { vexp }) => {
+                vexp.iter().flat_map(|e| e.targets()).collect()
+            }
+            Self::Not(ex) => ex.targets(),
+        }
+    }
 }
 
 #[derive(Debug)]
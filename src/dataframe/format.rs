@@ -25,7 +25,7 @@ impl TableFormatter {
             .map(|col| min(self.max_cell_display, col.name().len()))
             .collect::<Vec<usize>>();
         df.columns().iter().enumerate().for_each(|(i, col)| {
-            col.values().iter().for_each(|val| {
+            col.iter().for_each(|val| {
                 lengths[i] = min(
                     self.max_cell_display,
                     max(lengths[i], val.as_string().len()),
@@ -70,7 +70,7 @@ impl TableFormatter {
                 print!(
                     "| {} ",
                     pad_string(
-                        &df.columns()[col].values()[row].as_string(),
+                        &df.columns()[col].at(row).as_string(),
                         lengths[col],
                         df.columns()[col].typed().is_num()
                     )
@@ -1,14 +1,19 @@
-use std::collections::HashMap;
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    hash::{Hash, Hasher},
+};
 
 use crate::{
-    cell::{self, Cell, ToCell},
-    column::Col,
+    cell::{self, Cell},
+    column::{Col, ColSlice},
     dataframe::Dataframe,
     dataslice::DataSlice,
+    expression::Exp,
     util::Error,
 };
 
-#[derive(Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Copy)]
 pub enum Reducer {
     Count,
     Sum,
@@ -20,37 +25,125 @@ pub enum Reducer {
     Unique,
     Coalesce,
     NonNull,
+    Median,
+    // Population standard deviation and variance
+    Std,
+    Var,
+    // Linearly interpolated, `Quantile(0.25)` is the 25th percentile
+    Quantile(f64),
+    First,
+    Last,
+    Mode,
+}
+// Quantiles compare by their bits so reducers can key maps and sets
+impl PartialEq for Reducer {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Reducer::Quantile(x), Reducer::Quantile(y)) => x.to_bits() == y.to_bits(),
+            _ => std::mem::discriminant(self) == std::mem::discriminant(other),
+        }
+    }
+}
+impl Eq for Reducer {}
+impl Hash for Reducer {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        if let Reducer::Quantile(q) = self {
+            q.to_bits().hash(state);
+        }
+    }
 }
-struct Select {
-    column_name: String,
-    reducer: Reducer,
+type AggFn<'a> = Box<dyn Fn(&ColSlice) -> Cell + 'a>;
+type AggColsFn<'a> = Box<dyn Fn(&[ColSlice]) -> Cell + 'a>;
+
+enum Select<'a> {
+    Reduce(String, Reducer),
+    Agg(String, AggFn<'a>),
+    // Sees the group's columns in the listed order
+    AggCols(Vec<String>, AggColsFn<'a>),
+}
+
+impl Select<'_> {
+    // `cols` are the group's columns resolved for this select
+    fn apply(&self, cols: &[&Col]) -> Result<Cell, Error> {
+        match (self, cols.first()) {
+            (Select::Reduce(_, reducer), Some(col)) => reduce(*reducer, col),
+            (Select::Reduce(..), None) => Ok(Cell::Null(Box::new(Cell::Float(0.0)))),
+            (Select::Agg(_, f), _) => Ok(f(&ColSlice::from(cols[0]))),
+            (Select::AggCols(_, f), _) => Ok(f(&cols
+                .iter()
+                .map(|col| ColSlice::from(*col))
+                .collect::<Vec<ColSlice>>())),
+        }
+    }
 }
 pub struct DataGroup<'a> {
     slice: DataSlice<'a>,
-    by: String,
-    selects: Vec<Select>,
+    by: Vec<String>,
+    // Emit the key columns ahead of the selects
+    keys: bool,
+    selects: Vec<Select<'a>>,
     aliases: Vec<String>,
+    having: Vec<Exp>,
+    // Key subsets to group on instead of all of `by`, subtotal rows hold typed null keys
+    sets: Option<Vec<Vec<String>>>,
+    grouping_id: Option<String>,
 }
 
-struct ReduceRouter(HashMap<Reducer, fn(&Col) -> cell::Cell>);
-
 fn count(col: &Col) -> Cell {
     Cell::Uint(col.count() as u64)
 }
-fn sum(col: &Col) -> Cell {
-    col.sum().unwrap().to_cell()
+// Folds the non null values keeping the column's numeric type. Empty groups and non
+// numeric columns give a typed null, integer overflow is an error.
+fn fold_nums(
+    col: &Col,
+    int: fn(i64, i64) -> Option<i64>,
+    uint: fn(u64, u64) -> Option<u64>,
+    float: fn(f64, f64) -> f64,
+) -> Result<Cell, Error> {
+    let mut cells = col.iter().filter(|cell| !cell.is_null());
+    let first = match (col.typed().is_num(), cells.next()) {
+        (true, Some(first)) => first,
+        _ => return Ok(col.typed().null()),
+    };
+    match cells.try_fold(first, |acc, cell| match (acc, cell) {
+        (Cell::Int(x), Cell::Int(y)) => int(x, y).map(Cell::Int),
+        (Cell::Uint(x), Cell::Uint(y)) => uint(x, y).map(Cell::Uint),
+        (Cell::Float(x), Cell::Float(y)) => Some(Cell::Float(float(x, y))),
+        _ => None,
+    }) {
+        Some(cell) => Ok(cell),
+        None => Err(Error::new(format!(
+            "Integer overflow reducing column `{}`",
+            col.name()
+        ))),
+    }
+}
+fn sum(col: &Col) -> Result<Cell, Error> {
+    fold_nums(col, i64::checked_add, u64::checked_add, |x, y| x + y)
 }
-fn prod(col: &Col) -> Cell {
-    col.product().unwrap().to_cell()
+fn prod(col: &Col) -> Result<Cell, Error> {
+    fold_nums(col, i64::checked_mul, u64::checked_mul, |x, y| x * y)
 }
 fn mean(col: &Col) -> Cell {
-    col.mean().unwrap().to_cell()
+    stat(col.mean())
+}
+// Any column type, compared within the column's own type
+fn extreme(col: &Col, keep: Ordering) -> Cell {
+    let best = col
+        .iter()
+        .filter(|cell| !cell.is_null())
+        .reduce(|best, cell| match cell.partial_cmp(&best) {
+            Some(ord) if ord == keep => cell,
+            _ => best,
+        });
+    pick(col, best)
 }
 fn min(col: &Col) -> Cell {
-    col.min().unwrap().to_cell()
+    extreme(col, Ordering::Less)
 }
 fn max(col: &Col) -> Cell {
-    col.max().unwrap().to_cell()
+    extreme(col, Ordering::Greater)
 }
 fn top(col: &Col) -> Cell {
     match col.top() {
@@ -62,56 +155,234 @@ fn unique(col: &Col) -> Cell {
     cell::Cell::Uint(col.unique() as u64)
 }
 fn coalesce(col: &Col) -> Cell {
-    col.coalesce().unwrap().clone()
+    pick(col, col.iter().find(|cell| !cell.is_null()))
 }
 fn non_null(col: &Col) -> Cell {
     cell::Cell::Uint(col.non_null() as u64)
 }
 
-impl ReduceRouter {
-    fn new() -> ReduceRouter {
-        let mut map = HashMap::new();
-        map.insert(Reducer::Count, count as fn(&Col) -> cell::Cell);
-        map.insert(Reducer::Sum, sum as fn(&Col) -> cell::Cell);
-        map.insert(Reducer::Prod, prod as fn(&Col) -> cell::Cell);
-        map.insert(Reducer::Mean, mean as fn(&Col) -> cell::Cell);
-        map.insert(Reducer::Min, min as fn(&Col) -> cell::Cell);
-        map.insert(Reducer::Max, max as fn(&Col) -> cell::Cell);
-        map.insert(Reducer::Top, top as fn(&Col) -> cell::Cell);
-        map.insert(Reducer::Unique, unique as fn(&Col) -> cell::Cell);
-        map.insert(Reducer::Coalesce, coalesce as fn(&Col) -> cell::Cell);
-        map.insert(Reducer::NonNull, non_null as fn(&Col) -> cell::Cell);
-        ReduceRouter(map)
+// Float stats share their computation with `Col::describe`
+fn stat(val: Option<f64>) -> Cell {
+    match val {
+        Some(val) => Cell::Float(val),
+        None => cell::null_float(),
     }
 }
+fn pick(col: &Col, cell: Option<Cell>) -> Cell {
+    match cell {
+        Some(cell) => cell,
+        None => col.typed().null(),
+    }
+}
+
+fn reduce(reducer: Reducer, col: &Col) -> Result<Cell, Error> {
+    Ok(match reducer {
+        Reducer::Count => count(col),
+        Reducer::Sum => sum(col)?,
+        Reducer::Prod => prod(col)?,
+        Reducer::Mean => mean(col),
+        Reducer::Min => min(col),
+        Reducer::Max => max(col),
+        Reducer::Top => top(col),
+        Reducer::Unique => unique(col),
+        Reducer::Coalesce => coalesce(col),
+        Reducer::NonNull => non_null(col),
+        Reducer::Median => stat(col.median()),
+        Reducer::Std => stat(col.std()),
+        Reducer::Var => stat(col.var()),
+        Reducer::Quantile(q) => stat(col.quantile(q)),
+        Reducer::First => pick(col, col.first()),
+        Reducer::Last => pick(col, col.last()),
+        Reducer::Mode => pick(col, col.mode()),
+    })
+}
+
+// `sum(salary)` style aggregate names, quantiles take their fraction after the column
+fn parse_agg(target: &str) -> Option<(Reducer, &str)> {
+    let (name, column) = target.strip_suffix(')')?.split_once('(')?;
+    let reducer = match name {
+        "count" => Reducer::Count,
+        "sum" => Reducer::Sum,
+        "prod" => Reducer::Prod,
+        "mean" => Reducer::Mean,
+        "min" => Reducer::Min,
+        "max" => Reducer::Max,
+        "top" => Reducer::Top,
+        "unique" => Reducer::Unique,
+        "coalesce" => Reducer::Coalesce,
+        "non_null" => Reducer::NonNull,
+        "median" => Reducer::Median,
+        "std" => Reducer::Std,
+        "var" => Reducer::Var,
+        "first" => Reducer::First,
+        "last" => Reducer::Last,
+        "mode" => Reducer::Mode,
+        "quantile" => {
+            let (column, q) = column.rsplit_once(", ")?;
+            let q = q.parse::<f64>().ok().filter(|q| (0.0..=1.0).contains(q))?;
+            return Some((Reducer::Quantile(q), column));
+        }
+        _ => return None,
+    };
+    Some((reducer, column))
+}
 
 impl<'a> DataGroup<'a> {
     pub fn new(df: DataSlice<'a>, by: String) -> Self {
         DataGroup {
             slice: df,
-            by: by,
+            by: vec![by],
+            keys: false,
             selects: vec![],
             aliases: vec![],
+            having: vec![],
+            sets: None,
+            grouping_id: None,
+        }
+    }
+    // Groups on the tuple of `by` values, the result starts with the key columns
+    pub fn by_cols(df: DataSlice<'a>, by: Vec<String>) -> Self {
+        DataGroup {
+            slice: df,
+            by,
+            keys: true,
+            selects: vec![],
+            aliases: vec![],
+            having: vec![],
+            sets: None,
+            grouping_id: None,
         }
     }
     pub fn select(mut self, column: &str, reducer: Reducer, to_name: &str) -> Self {
-        self.selects.push(Select {
-            column_name: column.to_string(),
-            reducer: reducer,
-        });
+        self.selects
+            .push(Select::Reduce(column.to_string(), reducer));
         self.aliases.push(to_name.to_string());
         self
     }
     pub fn select_strings(mut self, column: String, reducer: Reducer, to_name: String) -> Self {
-        self.selects.push(Select {
-            column_name: column,
-            reducer: reducer,
-        });
+        self.selects.push(Select::Reduce(column, reducer));
         self.aliases.push(to_name);
         self
     }
+    // Reduces the group's `column` with a closure, every group must return the same type
+    pub fn agg<F>(mut self, column: &str, to_name: &str, f: F) -> Self
+    where
+        F: Fn(&ColSlice) -> Cell + 'a,
+    {
+        self.selects
+            .push(Select::Agg(column.to_string(), Box::new(f)));
+        self.aliases.push(to_name.to_string());
+        self
+    }
+    // Like `agg` over several columns, e.g. a weighted mean
+    pub fn agg_cols<F>(mut self, columns: &[&str], to_name: &str, f: F) -> Self
+    where
+        F: Fn(&[ColSlice]) -> Cell + 'a,
+    {
+        self.selects.push(Select::AggCols(
+            columns.iter().map(|c| c.to_string()).collect(),
+            Box::new(f),
+        ));
+        self.aliases.push(to_name.to_string());
+        self
+    }
+    // Keeps groups matching `exp`, which can read key columns, the grouping id, aliases and
+    // unselected aggregates named like `sum(salary)` or `quantile(salary, 0.9)`
+    pub fn having(mut self, exp: Exp) -> Self {
+        self.having.push(exp);
+        self
+    }
+    // Groups on every prefix of the keys, from all keys down to the grand total
+    pub fn rollup(mut self) -> Self {
+        self.sets = Some(
+            (0..=self.by.len())
+                .rev()
+                .map(|i| self.by[..i].to_vec())
+                .collect(),
+        );
+        self
+    }
+    // Groups on every subset of the keys, from all keys down to the grand total
+    pub fn cube(mut self) -> Self {
+        let len = self.by.len();
+        self.sets = Some(
+            (0..1_usize << len)
+                .map(|mask| {
+                    (0..len)
+                        .filter(|i| mask & (1 << (len - 1 - i)) == 0)
+                        .map(|i| self.by[i].clone())
+                        .collect()
+                })
+                .collect(),
+        );
+        self
+    }
+    pub fn grouping_sets(mut self, sets: Vec<Vec<&str>>) -> Self {
+        self.sets = Some(
+            sets.iter()
+                .map(|set| set.iter().map(|b| b.to_string()).collect())
+                .collect(),
+        );
+        self
+    }
+    // Adds a Uint column after the keys with a bit set for each key left out of the row's
+    // grouping set, the first key being the highest bit
+    pub fn grouping_id(mut self, to_name: &str) -> Self {
+        self.grouping_id = Some(to_name.to_string());
+        self
+    }
     pub fn to_dataframe(self) -> Result<Dataframe, Error> {
-        let rd_router = ReduceRouter::new();
+        match &self.sets {
+            Some(sets) => self.aggregate(sets, true),
+            None => self.aggregate(std::slice::from_ref(&self.by), self.keys),
+        }
+        .map(|(_, df)| df)
+    }
+
+    // Each aggregate broadcast to the rows of its group, appended to the original columns
+    pub fn transform(self) -> Result<Dataframe, Error> {
+        let (groups, reduced) = self.aggregate(std::slice::from_ref(&self.by), false)?;
+        // Rows of groups dropped by `having` are dropped too
+        let mut rows = groups
+            .iter()
+            .enumerate()
+            .flat_map(|(group, rows)| rows.iter().map(move |row| (*row, group)))
+            .collect::<Vec<(usize, usize)>>();
+        rows.sort_unstable();
+        let (rows, row_group): (Vec<usize>, Vec<usize>) = rows.into_iter().unzip();
+        let mut columns = self.slice.take(&rows).columns().clone();
+        for col in reduced.columns() {
+            if columns.iter().any(|c| c.name() == col.name()) {
+                return Err(Error::new("Col names must be unique".to_string()));
+            }
+            columns.push(col.gather(&row_group));
+        }
+        Dataframe::new(Some(self.slice.title())).set_columns(columns)
+    }
+
+    // Runs `f` on each group's rows and concatenates the results in group order
+    pub fn apply<F>(self, mut f: F) -> Result<Dataframe, Error>
+    where
+        F: FnMut(Dataframe) -> Dataframe,
+    {
+        let mut results = self
+            .aggregate(std::slice::from_ref(&self.by), false)?
+            .0
+            .into_iter()
+            .map(|rows| f(self.slice.take(&rows)));
+        let mut df = results.next().unwrap_or_else(|| Dataframe::new(None));
+        for result in results {
+            df.concat(result)?;
+        }
+        Ok(df)
+    }
+
+    // Row indices of each group and its reduced row, for each grouping set in turn
+    fn aggregate(
+        &self,
+        sets: &[Vec<String>],
+        keys: bool,
+    ) -> Result<(Vec<Vec<usize>>, Dataframe), Error> {
         let name_indices = self
             .slice
             .columns()
@@ -119,24 +390,212 @@ impl<'a> DataGroup<'a> {
             .enumerate()
             .map(|(i, col)| (col.name(), i))
             .collect::<HashMap<&str, usize>>();
-        Dataframe::from_string_rows(
-            self.aliases,
-            self.slice
-                .chunk_by(&self.by)?
+        let col_idx = |name: &String| match name_indices.get(name.as_str()) {
+            Some(idx) => Ok(*idx),
+            None => Err(Error::new(format!("Group agg col `{}` not found", name))),
+        };
+        // Closures need their columns, a missing reducer column selects nulls
+        let indices = self
+            .selects
+            .iter()
+            .map(|select| match select {
+                Select::Reduce(name, _) => Ok(name_indices
+                    .get(name.as_str())
+                    .copied()
+                    .into_iter()
+                    .collect()),
+                Select::Agg(name, _) => Ok(vec![col_idx(name)?]),
+                Select::AggCols(names, _) => names.iter().map(col_idx).collect(),
+            })
+            .collect::<Result<Vec<Vec<usize>>, Error>>()?;
+        // Having targets other than aliases and keys are `reducer(column)` aggregates
+        let mut having_aggs: HashMap<&str, (Reducer, usize)> = HashMap::new();
+        for target in self.having.iter().flat_map(|exp| exp.targets()) {
+            if self
+                .aliases
+                .iter()
+                .chain(self.by.iter())
+                .chain(self.grouping_id.iter())
+                .any(|name| name == target)
+            {
+                continue;
+            }
+            match parse_agg(target)
+                .and_then(|(reducer, name)| Some((reducer, *name_indices.get(name)?)))
+            {
+                Some(agg) => having_aggs.insert(target, agg),
+                None => return Err(Error::new(format!("Having target `{}` not found", target))),
+            };
+        }
+        let mut labels = match keys {
+            true => self.by.clone(),
+            false => vec![],
+        };
+        if let (true, Some(grouping_id)) = (keys, &self.grouping_id) {
+            labels.push(grouping_id.clone());
+        }
+        // A key selected under its own name, like `select("region", Coalesce, "region")`,
+        // is already emitted as a key column with null subtotal cells
+        let reselected = self
+            .selects
+            .iter()
+            .zip(self.aliases.iter())
+            .map(|(select, alias)| match select {
+                Select::Reduce(column, _) => keys && column == alias && self.by.contains(alias),
+                _ => false,
+            })
+            .collect::<Vec<bool>>();
+        let kept = |i: &usize| !reselected[*i];
+        labels.extend(
+            self.aliases
                 .iter()
-                .map(|df| {
-                    self.selects
+                .enumerate()
+                .filter(|(i, _)| kept(i))
+                .map(|(_, alias)| alias.clone()),
+        );
+        if self
+            .by
+            .iter()
+            .any(|b| !name_indices.contains_key(b.as_str()))
+        {
+            return Err(Error::new("Group by col not found".to_string()));
+        }
+        let mut groups = vec![];
+        let mut rows = vec![];
+        for set in sets {
+            if let Some(name) = set.iter().find(|name| !self.by.contains(name)) {
+                return Err(Error::new(format!(
+                    "Grouping set col `{}` is not a group by col",
+                    name
+                )));
+            }
+            let set = set.iter().map(|b| b.as_str()).collect::<Vec<&str>>();
+            let grouping_id = self
+                .by
+                .iter()
+                .fold(0_u64, |id, b| (id << 1) | !set.contains(&b.as_str()) as u64);
+            let mut set_groups = self.slice.group_indices(&set)?;
+            // The grand total has a row even without input rows
+            if set.is_empty() && set_groups.is_empty() {
+                set_groups.push(vec![]);
+            }
+            for group in set_groups {
+                let df = self.slice.take(&group);
+                let key = self
+                    .by
+                    .iter()
+                    .map(|b| match set.contains(&b.as_str()) {
+                        true => df.columns()[name_indices[b.as_str()]].at(0),
+                        false => df.columns()[name_indices[b.as_str()]].typed().null(),
+                    })
+                    .collect::<Vec<Cell>>();
+                let cells = self
+                    .selects
+                    .iter()
+                    .zip(indices.iter())
+                    .map(|(select, idx)| {
+                        select.apply(&idx.iter().map(|i| &df.columns()[*i]).collect::<Vec<&Col>>())
+                    })
+                    .collect::<Result<Vec<Cell>, Error>>()?;
+                if !self.having.is_empty() {
+                    let mut against = self
+                        .by
                         .iter()
-                        .map(|select| {
-                            if let Some(idx) = name_indices.get(select.column_name.as_str()) {
-                                rd_router.0.get(&select.reducer).unwrap()(&df.columns()[*idx])
-                            } else {
-                                Cell::Null(Box::new(Cell::Float(0.0)))
-                            }
-                        })
-                        .collect::<Vec<Cell>>()
-                })
-                .collect::<Vec<Vec<Cell>>>(),
+                        .cloned()
+                        .zip(key.iter().cloned())
+                        .chain(
+                            self.aliases
+                                .iter()
+                                .cloned()
+                                .zip(cells.iter().cloned())
+                                .enumerate()
+                                .filter(|(i, _)| kept(i))
+                                .map(|(_, alias)| alias),
+                        )
+                        .collect::<HashMap<String, Cell>>();
+                    if let Some(name) = &self.grouping_id {
+                        against.insert(name.clone(), Cell::Uint(grouping_id));
+                    }
+                    for (target, (reducer, idx)) in having_aggs.iter() {
+                        against.insert(target.to_string(), reduce(*reducer, &df.columns()[*idx])?);
+                    }
+                    let against = against.iter().map(|(k, v)| (k.clone(), v)).collect();
+                    if !self.having.iter().all(|exp| exp.evaluate(&against)) {
+                        continue;
+                    }
+                }
+                let mut row = match keys {
+                    true => key,
+                    false => vec![],
+                };
+                if let (true, Some(_)) = (keys, &self.grouping_id) {
+                    row.push(Cell::Uint(grouping_id));
+                }
+                row.extend(
+                    cells
+                        .into_iter()
+                        .enumerate()
+                        .filter(|(i, _)| kept(i))
+                        .map(|(_, cell)| cell),
+                );
+                rows.push(row);
+                groups.push(group);
+            }
+        }
+        if rows.is_empty() {
+            return Ok((
+                groups,
+                self.empty_result(labels, &indices, &reselected, keys)?,
+            ));
+        }
+        let reduced = Dataframe::from_string_rows(labels, rows)?;
+        Ok((groups, reduced))
+    }
+    // Without rows the column types come from the keys and from reducing an empty group,
+    // closures can't be asked so their columns are floats
+    fn empty_result(
+        &self,
+        labels: Vec<String>,
+        indices: &[Vec<usize>],
+        reselected: &[bool],
+        keys: bool,
+    ) -> Result<Dataframe, Error> {
+        let empty = self.slice.take(&[]);
+        let mut types = vec![];
+        if keys {
+            types.extend(self.by.iter().map(|b| {
+                let idx = self.slice.columns().iter().position(|col| col.name() == b);
+                self.slice.columns()[idx.unwrap()].typed().clone()
+            }));
+            if self.grouping_id.is_some() {
+                types.push(Cell::Uint(0));
+            }
+        }
+        for ((select, idx), _) in self
+            .selects
+            .iter()
+            .zip(indices.iter())
+            .zip(reselected.iter())
+            .filter(|(_, reselected)| !**reselected)
+        {
+            let cols = idx
+                .iter()
+                .map(|i| &empty.columns()[*i])
+                .collect::<Vec<&Col>>();
+            types.push(match select {
+                Select::Reduce(..) => match select.apply(&cols)? {
+                    Cell::Null(typed) => *typed,
+                    cell => cell,
+                },
+                _ => Cell::Float(0.0),
+            });
+        }
+        Dataframe::new(None).set_columns(
+            labels
+                .into_iter()
+                .zip(types)
+                .map(|(label, typed)| Col::build(label, vec![], typed))
+                .collect(),
         )
     }
 }
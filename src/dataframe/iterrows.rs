@@ -1,5 +1,13 @@
-use crate::{cell::Cell, dataframe::Dataframe, dataslice::*};
-use std::collections::HashMap;
+use crate::{
+    cell::Cell,
+    csv_io::{self, CsvOptions},
+    dataframe::Dataframe,
+    dataslice::*,
+    util::Error,
+};
+use csv::{Reader, StringRecord};
+use std::collections::{HashMap, VecDeque};
+use std::io::Read;
 pub struct Iterrows<'a> {
     data_slice: DataSlice<'a>,
     index: usize,
@@ -22,7 +30,7 @@ impl<'a> Iterator for Iterrows<'a> {
                 self.data_slice
                     .columns()
                     .iter()
-                    .map(|col| (col.name().to_string(), &col.values()[self.index - 1]))
+                    .map(|col| (col.name().to_string(), col.cell(self.index - 1).unwrap()))
                     .collect::<HashMap<String, &'a Cell>>(),
             )
         } else {
@@ -33,22 +41,27 @@ impl<'a> Iterator for Iterrows<'a> {
 
 pub struct IntoIterrows {
     dataframe: Dataframe,
+    index: usize,
 }
 impl IntoIterrows {
     pub fn new(df: Dataframe) -> Self {
-        Self { dataframe: df }
+        Self {
+            dataframe: df,
+            index: 0,
+        }
     }
 }
 impl Iterator for IntoIterrows {
     type Item = HashMap<String, Cell>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.dataframe.length() > 0 {
+        if self.index < self.dataframe.length() {
+            self.index += 1;
             Some(
                 self.dataframe
-                    .columns_mut()
-                    .iter_mut()
-                    .map(|col| (col.name().to_string(), col.values_mut().remove(0)))
+                    .columns()
+                    .iter()
+                    .map(|col| (col.name().to_string(), col.at(self.index - 1)))
                     .collect::<HashMap<String, Cell>>(),
             )
         } else {
@@ -101,6 +114,96 @@ impl<'a> Iterator for IterChunk<'a> {
     }
 }
 
+// CSV chunk
+pub struct ScanCsv<R: Read> {
+    reader: Reader<R>,
+    options: CsvOptions,
+    labels: Vec<String>,
+    types: Vec<Cell>,
+    // records read ahead to infer the schema
+    sampled: VecDeque<StringRecord>,
+    chunk_size: usize,
+    index: usize,
+    done: bool,
+}
+impl<R: Read> ScanCsv<R> {
+    pub fn new(reader: R, size: usize, options: &CsvOptions) -> Result<Self, Error> {
+        let mut reader = options.reader().from_reader(reader);
+        let labels = csv_io::read_labels(&mut reader, options)?;
+        let mut sampled = VecDeque::new();
+        for record in reader.records().take(options.sample_size()) {
+            match record {
+                Ok(r) => sampled.push_back(r),
+                Err(e) => return Err(Error::new(e.to_string())),
+            }
+        }
+        let types = csv_io::infer_schema(&labels, sampled.make_contiguous(), options);
+        Ok(Self {
+            reader,
+            options: options.clone(),
+            labels,
+            types,
+            sampled,
+            chunk_size: std::cmp::max(size, 1),
+            index: 0,
+            done: false,
+        })
+    }
+    pub fn labels(&self) -> &Vec<String> {
+        &self.labels
+    }
+    pub fn types(&self) -> &Vec<Cell> {
+        &self.types
+    }
+    fn next_records(&mut self) -> Result<Vec<StringRecord>, Error> {
+        let mut records = Vec::with_capacity(self.chunk_size);
+        while records.len() < self.chunk_size {
+            if let Some(record) = self.sampled.pop_front() {
+                records.push(record);
+                continue;
+            }
+            let mut record = StringRecord::new();
+            match self.reader.read_record(&mut record) {
+                Ok(true) => records.push(record),
+                Ok(false) => break,
+                Err(e) => return Err(Error::new(e.to_string())),
+            }
+        }
+        Ok(records)
+    }
+}
+impl<R: Read> Iterator for ScanCsv<R> {
+    type Item = Result<Dataframe, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let chunk = self.next_records().and_then(|records| {
+            let cols = csv_io::build_cols(
+                &self.labels,
+                &self.types,
+                &records,
+                self.index,
+                &self.options,
+            )?;
+            self.index += records.len();
+            Ok((records.len(), cols))
+        });
+        match chunk {
+            Ok((0, _)) => {
+                self.done = true;
+                None
+            }
+            Ok((_, cols)) => Some(Dataframe::new(None).set_columns(cols)),
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
 // SQL
 pub struct IterSQL<'a, 'b> {
     data_slice: DataSlice<'a>,
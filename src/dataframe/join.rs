@@ -1,6 +1,343 @@
-// enum Join {
-//     Left,
-//     Right,
-//     Inner,
-//     Union,
-// }
+use crate::{cell::*, column::Col, util::Error};
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JoinType {
+    Inner,
+    Left,
+    Right,
+    Full,
+    Cross,
+    // Left rows with at least one match, left columns only
+    Semi,
+    // Left rows without a match, left columns only
+    Anti,
+}
+
+#[derive(Debug, Clone)]
+pub struct JoinOptions {
+    suffixes: (String, String),
+    keep_keys: bool,
+    nulls_equal: bool,
+}
+
+impl Default for JoinOptions {
+    fn default() -> Self {
+        JoinOptions {
+            suffixes: ("_left".to_string(), "_right".to_string()),
+            keep_keys: false,
+            nulls_equal: false,
+        }
+    }
+}
+
+impl JoinOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    // Appended to column names found on both sides
+    pub fn suffixes(mut self, left: &str, right: &str) -> Self {
+        self.suffixes = (left.to_string(), right.to_string());
+        self
+    }
+    // Keep the right key columns instead of merging them into the left ones
+    pub fn keep_keys(mut self, keep_keys: bool) -> Self {
+        self.keep_keys = keep_keys;
+        self
+    }
+
+    // Null keys match each other, by default rows with a null key are never matched
+    pub fn nulls_equal(mut self, nulls_equal: bool) -> Self {
+        self.nulls_equal = nulls_equal;
+        self
+    }
+
+    pub(crate) fn keeps_keys(&self) -> bool {
+        self.keep_keys
+    }
+    pub(crate) fn nulls_match(&self) -> bool {
+        self.nulls_equal
+    }
+}
+
+// Suffixes column names found on both sides
+pub(crate) fn merge_columns(
+    mut columns: Vec<Col>,
+    mut with_columns: Vec<Col>,
+    options: &JoinOptions,
+) -> Result<Vec<Col>, Error> {
+    let left_names = columns
+        .iter()
+        .map(|col| col.name().to_string())
+        .collect::<HashSet<String>>();
+    let right_names = with_columns
+        .iter()
+        .map(|col| col.name().to_string())
+        .collect::<HashSet<String>>();
+    for col in columns.iter_mut() {
+        if right_names.contains(col.name()) {
+            col.rename(format!("{}{}", col.name(), options.suffixes.0));
+        }
+    }
+    for col in with_columns.iter_mut() {
+        if left_names.contains(col.name()) {
+            col.rename(format!("{}{}", col.name(), options.suffixes.1));
+        }
+    }
+    columns.extend(with_columns);
+    // Suffixed names can still collide with existing columns
+    let names = columns
+        .iter()
+        .map(|col| col.name())
+        .collect::<HashSet<&str>>();
+    if names.len() != columns.len() {
+        return Err(Error::new(
+            "Join dataframe columns are not unique".to_string(),
+        ));
+    }
+    Ok(columns)
+}
+
+// Numbers match across Int, Uint and Float when they hold the same value and nulls of any
+// type are the same key
+fn key_cell(cell: Cell) -> Cell {
+    match cell {
+        Cell::Uint(x) if x <= i64::MAX as u64 => Cell::Int(x as i64),
+        Cell::Float(x) if x.fract() == 0.0 && x >= i64::MIN as f64 && x < i64::MAX as f64 => {
+            Cell::Int(x as i64)
+        }
+        Cell::Null(_) => null_int(),
+        cell => cell,
+    }
+}
+
+// Right key cell in the left key column's type when its value survives the cast. Values
+// that don't fit, like a fractional float against an int key, keep their own type.
+pub(crate) fn cast_key(cell: Cell, typed: &Cell) -> Cell {
+    match (typed.zero(), key_cell(cell.clone())) {
+        (_, Cell::Null(_)) => typed.null(),
+        (Cell::Int(_), Cell::Int(x)) => Cell::Int(x),
+        (Cell::Uint(_), Cell::Int(x)) if x >= 0 => Cell::Uint(x as u64),
+        (Cell::Float(_), Cell::Int(x)) => Cell::Float(x as f64),
+        _ => cell,
+    }
+}
+
+// Composite key of one row, matched on the tuple of cell values. `None` when a null key
+// can't match anything.
+fn row_key(cols: &[&Col], i: usize, nulls_equal: bool) -> Option<Vec<Cell>> {
+    cols.iter()
+        .map(|col| match col.is_null(i) && !nulls_equal {
+            true => None,
+            false => Some(key_cell(col.at(i))),
+        })
+        .collect()
+}
+
+fn key_map(cols: &[&Col], len: usize, nulls_equal: bool) -> HashMap<Vec<Cell>, Vec<usize>> {
+    let mut map: HashMap<Vec<Cell>, Vec<usize>> = HashMap::new();
+    (0..len).for_each(|i| {
+        if let Some(key) = row_key(cols, i, nulls_equal) {
+            map.entry(key).or_default().push(i);
+        }
+    });
+    map
+}
+
+// Row pairs to gather from each side, `None` marks a row missing on that side
+pub(crate) fn join_indices(
+    left: (&[&Col], usize),
+    right: (&[&Col], usize),
+    how: JoinType,
+    nulls_equal: bool,
+) -> (Vec<Option<usize>>, Vec<Option<usize>>) {
+    let ((left, left_len), (right, right_len)) = (left, right);
+    let mut left_idx = vec![];
+    let mut right_idx = vec![];
+    let mut push = |i: Option<usize>, j: Option<usize>| {
+        left_idx.push(i);
+        right_idx.push(j);
+    };
+    match how {
+        JoinType::Cross => {
+            (0..left_len).for_each(|i| (0..right_len).for_each(|j| push(Some(i), Some(j))))
+        }
+        // Keeps the order of the right rows
+        JoinType::Right => {
+            let left_map = key_map(left, left_len, nulls_equal);
+            (0..right_len).for_each(|j| {
+                match row_key(right, j, nulls_equal).and_then(|key| left_map.get(&key)) {
+                    Some(indices) => indices.iter().for_each(|i| push(Some(*i), Some(j))),
+                    None => push(None, Some(j)),
+                }
+            });
+        }
+        _ => {
+            let right_map = key_map(right, right_len, nulls_equal);
+            let mut matched = vec![false; right_len];
+            (0..left_len).for_each(|i| {
+                let indices = row_key(left, i, nulls_equal).and_then(|key| right_map.get(&key));
+                match (indices, how) {
+                    (Some(_), JoinType::Anti) | (None, JoinType::Inner | JoinType::Semi) => (),
+                    (Some(_), JoinType::Semi) | (None, JoinType::Anti) => push(Some(i), None),
+                    (Some(indices), _) => indices.iter().for_each(|j| {
+                        matched[*j] = true;
+                        push(Some(i), Some(*j))
+                    }),
+                    (None, _) => push(Some(i), None),
+                }
+            });
+            // Unmatched right rows follow the left rows
+            if how == JoinType::Full {
+                (0..right_len)
+                    .filter(|j| !matched[*j])
+                    .for_each(|j| push(None, Some(j)));
+            }
+        }
+    }
+    (left_idx, right_idx)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AsofDirection {
+    // Last right key at or before the left key
+    Backward,
+    // First right key at or after the left key
+    Forward,
+    // Closest right key, ties go backward
+    Nearest,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AsofOptions {
+    tolerance: Option<f64>,
+    by: Option<(String, String)>,
+}
+
+impl AsofOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    // Largest distance between matched keys, in seconds for DateTime keys
+    pub fn tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = Some(tolerance);
+        self
+    }
+    // Only match rows with equal values in these (left, right) columns
+    pub fn by(mut self, left: &str, right: &str) -> Self {
+        self.by = Some((left.to_string(), right.to_string()));
+        self
+    }
+
+    pub(crate) fn by_cols(&self) -> Option<(&str, &str)> {
+        self.by
+            .as_ref()
+            .map(|(left, right)| (left.as_str(), right.as_str()))
+    }
+}
+
+// Position of an as-of key on a number line as whole units plus a fraction in 0..1, so
+// large Int and Uint keys compare exactly. DateTime keys count nanoseconds, nulls and
+// non finite floats have no position.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+struct AsofKey(i128, f64);
+
+impl AsofKey {
+    fn from_cell(cell: &Cell) -> Option<Self> {
+        match cell {
+            Cell::Int(x) => Some(AsofKey(*x as i128, 0.0)),
+            Cell::Uint(x) => Some(AsofKey(*x as i128, 0.0)),
+            Cell::Float(x) if x.is_finite() => Some(AsofKey(x.floor() as i128, x - x.floor())),
+            Cell::DateTime(x) => {
+                let at = x.and_utc();
+                Some(AsofKey(
+                    at.timestamp() as i128 * 1_000_000_000 + at.timestamp_subsec_nanos() as i128,
+                    0.0,
+                ))
+            }
+            _ => None,
+        }
+    }
+    // Signed distance, only rounded once it is small
+    fn minus(self, other: AsofKey) -> f64 {
+        (self.0 - other.0) as f64 + (self.1 - other.1)
+    }
+}
+
+fn check_asof_key(col: &Col) -> Result<(), Error> {
+    match col.typed().zero() {
+        Cell::Int(_) | Cell::Uint(_) | Cell::Float(_) | Cell::DateTime(_) => Ok(()),
+        _ => Err(Error::new(format!(
+            "As-of join key `{}` must be numeric or DateTime",
+            col.name()
+        ))),
+    }
+}
+
+// Right row matched by each left row
+pub(crate) fn asof_indices(
+    left: &Col,
+    right: &Col,
+    by: Option<(&Col, &Col)>,
+    direction: AsofDirection,
+    options: &AsofOptions,
+) -> Result<Vec<Option<usize>>, Error> {
+    check_asof_key(left)?;
+    check_asof_key(right)?;
+    if cell_is_date_time(&left.typed().zero()) != cell_is_date_time(&right.typed().zero()) {
+        return Err(Error::new(
+            "As-of join keys must both be numeric or both DateTime".to_string(),
+        ));
+    }
+    let group_key = |col: Option<&Col>, i: usize| match col {
+        Some(col) if col.is_null(i) => None,
+        Some(col) => Some(key_cell(col.at(i))),
+        None => Some(null_int()),
+    };
+    // Right keys sorted per group, equal keys keep their row order
+    let mut groups: HashMap<Cell, Vec<(AsofKey, usize)>> = HashMap::new();
+    for j in 0..right.len() {
+        if let (Some(group), Some(val)) = (
+            group_key(by.map(|(_, right_by)| right_by), j),
+            AsofKey::from_cell(&right.at(j)),
+        ) {
+            groups.entry(group).or_default().push((val, j));
+        }
+    }
+    groups
+        .values_mut()
+        .for_each(|keys| keys.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap()));
+    let scale = match cell_is_date_time(&left.typed().zero()) {
+        true => 1_000_000_000.0,
+        false => 1.0,
+    };
+    let within = |x: AsofKey, (val, j): (AsofKey, usize)| match options.tolerance {
+        Some(tolerance) if x.minus(val).abs() > tolerance * scale => None,
+        _ => Some((val, j)),
+    };
+    Ok((0..left.len())
+        .map(|i| {
+            let x = AsofKey::from_cell(&left.at(i))?;
+            let keys = groups.get(&group_key(by.map(|(left_by, _)| left_by), i)?)?;
+            let before = match keys.partition_point(|(val, _)| *val <= x) {
+                0 => None,
+                idx => within(x, keys[idx - 1]),
+            };
+            let after = keys
+                .get(keys.partition_point(|(val, _)| *val < x))
+                .and_then(|key| within(x, *key));
+            let matched = match (direction, before, after) {
+                (AsofDirection::Backward, before, _) => before,
+                (AsofDirection::Forward, _, after) => after,
+                (AsofDirection::Nearest, Some(before), Some(after)) => {
+                    match x.minus(before.0) <= after.0.minus(x) {
+                        true => Some(before),
+                        false => Some(after),
+                    }
+                }
+                (AsofDirection::Nearest, before, after) => before.or(after),
+            };
+            matched.map(|(_, j)| j)
+        })
+        .collect())
+}
@@ -0,0 +1,186 @@
+use crate::{cell::*, column::Col, util::Error};
+use chrono::NaiveDateTime;
+use serde_json::{Map, Number, Value};
+
+// How `NaiveDateTime` displays, so `to_json` output reads back as DateTime
+const DATE_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.f";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JsonOrient {
+    // [{"col": val, ..}, ..]
+    Records,
+    // {"col": [val, ..], ..}
+    Columns,
+}
+
+pub(crate) fn cell_to_value(cell: &Cell) -> Value {
+    match cell {
+        Cell::Int(x) => Value::from(*x),
+        Cell::Uint(x) => Value::from(*x),
+        Cell::Float(x) => match Number::from_f64(*x) {
+            Some(n) => Value::Number(n),
+            None => Value::Null,
+        },
+        Cell::Str(x) => Value::String(x.clone()),
+        Cell::Bool(x) => Value::Bool(*x),
+        Cell::DateTime(x) => Value::String(x.to_string()),
+        Cell::Null(_) => Value::Null,
+    }
+}
+
+pub(crate) fn record(cols: &[Col], i: usize) -> Value {
+    Value::Object(
+        cols.iter()
+            .map(|col| (col.name().to_string(), cell_to_value(&col.at(i))))
+            .collect(),
+    )
+}
+
+pub(crate) fn columns(cols: &[Col]) -> Value {
+    Value::Object(
+        cols.iter()
+            .map(|col| {
+                (
+                    col.name().to_string(),
+                    Value::Array(col.iter().map(|cell| cell_to_value(&cell)).collect()),
+                )
+            })
+            .collect(),
+    )
+}
+
+fn value_to_cell(value: &Value, col: &str) -> Result<Option<Cell>, Error> {
+    match value {
+        Value::Null => Ok(None),
+        Value::Bool(x) => Ok(Some(Cell::Bool(*x))),
+        Value::String(x) => Ok(Some(Cell::Str(x.clone()))),
+        Value::Number(x) => Ok(match (x.as_i64(), x.as_u64()) {
+            (Some(v), _) => Some(Cell::Int(v)),
+            (None, Some(v)) => Some(Cell::Uint(v)),
+            _ => x.as_f64().map(Cell::Float),
+        }),
+        _ => Err(Error::new(format!(
+            "Nested JSON value in column `{col}` is not supported"
+        ))),
+    }
+}
+
+// Numbers share a column by widening Int/Uint into Float, any other mix is an error
+fn unify(col: &str, cells: &[Option<Cell>]) -> Result<Cell, Error> {
+    let mut typed: Option<Cell> = None;
+    let mut has_negative = false;
+    for cell in cells.iter().flatten() {
+        if let Cell::Int(x) = cell {
+            has_negative = has_negative || *x < 0;
+        }
+        let zero = cell.zero();
+        typed = Some(match typed {
+            None => zero,
+            Some(cur) if cur == zero => cur,
+            Some(cur) if cur.is_num() && zero.is_num() => match (&cur, &zero) {
+                (Cell::Float(_), _) | (_, Cell::Float(_)) => Cell::Float(0.0),
+                _ if has_negative => Cell::Float(0.0),
+                _ => Cell::Uint(0),
+            },
+            Some(cur) => {
+                return Err(Error::new(format!(
+                    "Heterogeneous column `{col}`: {} and {}",
+                    cur.type_string(),
+                    zero.type_string()
+                )))
+            }
+        });
+    }
+    Ok(typed.unwrap_or(Cell::Str(String::new())))
+}
+
+fn convert(cell: Option<Cell>, typed: &Cell) -> Cell {
+    match (cell, typed) {
+        (None, _) => typed.null(),
+        (Some(cell), Cell::Float(_)) => cell.to_float(),
+        (Some(Cell::Int(x)), Cell::Uint(_)) => Cell::Uint(x as u64),
+        (Some(cell), _) => cell,
+    }
+}
+
+fn parse_date_time(cell: &Cell) -> Option<NaiveDateTime> {
+    match cell {
+        Cell::Str(x) => NaiveDateTime::parse_from_str(x, DATE_TIME_FORMAT).ok(),
+        _ => None,
+    }
+}
+
+// String columns where every value is a timestamp become DateTime columns
+fn date_times(cells: &[Option<Cell>]) -> Option<Vec<Option<Cell>>> {
+    if cells.iter().all(|cell| cell.is_none()) {
+        return None;
+    }
+    cells
+        .iter()
+        .map(|cell| match cell {
+            Some(cell) => parse_date_time(cell).map(|x| Some(Cell::DateTime(x))),
+            None => Some(None),
+        })
+        .collect()
+}
+
+fn build_col(name: &str, cells: Vec<Option<Cell>>) -> Result<Col, Error> {
+    let mut typed = unify(name, &cells)?;
+    let cells = match (&typed, date_times(&cells)) {
+        (Cell::Str(_), Some(date_times)) => {
+            typed = Cell::DateTime(NaiveDateTime::default()).zero();
+            date_times
+        }
+        _ => cells,
+    };
+    let values = cells
+        .into_iter()
+        .map(|cell| convert(cell, &typed))
+        .collect();
+    Ok(Col::build(name.to_string(), values, typed))
+}
+
+pub(crate) fn records_to_cols(records: &[Value]) -> Result<Vec<Col>, Error> {
+    let mut labels: Vec<String> = vec![];
+    let mut cols: Vec<Vec<Option<Cell>>> = vec![];
+    for (i, record) in records.iter().enumerate() {
+        let record = match record {
+            Value::Object(map) => map,
+            _ => return Err(Error::new(format!("JSON record {i} is not an object"))),
+        };
+        for key in record.keys() {
+            if !labels.contains(key) {
+                labels.push(key.clone());
+                // Earlier records did not have this key
+                cols.push(vec![None; i]);
+            }
+        }
+        for (j, label) in labels.iter().enumerate() {
+            cols[j].push(match record.get(label) {
+                Some(value) => value_to_cell(value, label)?,
+                None => None,
+            });
+        }
+    }
+    labels
+        .iter()
+        .zip(cols)
+        .map(|(label, cells)| build_col(label, cells))
+        .collect()
+}
+
+pub(crate) fn columns_to_cols(columns: &Map<String, Value>) -> Result<Vec<Col>, Error> {
+    columns
+        .iter()
+        .map(|(label, values)| match values {
+            Value::Array(values) => build_col(
+                label,
+                values
+                    .iter()
+                    .map(|value| value_to_cell(value, label))
+                    .collect::<Result<Vec<Option<Cell>>, Error>>()?,
+            ),
+            _ => Err(Error::new(format!("JSON column `{label}` is not an array"))),
+        })
+        .collect()
+}
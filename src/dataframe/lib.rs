@@ -1,5 +1,9 @@
+#[cfg(feature = "arrow")]
+mod arrow_io;
+mod buffer;
 pub mod cell;
 mod column;
+mod csv_io;
 pub mod dataframe;
 mod dataslice;
 pub mod expression;
@@ -7,6 +11,9 @@ mod format;
 pub mod group;
 mod iterrows;
 mod join;
+mod json_io;
+#[cfg(feature = "parquet")]
+mod parquet_io;
 pub mod row;
 pub mod sort;
 mod util;
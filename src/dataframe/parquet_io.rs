@@ -0,0 +1,369 @@
+use crate::{cell::*, column::Col, util::Error};
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use parquet::{
+    basic::{LogicalType, Repetition, TimeUnit, Type as PhysicalType},
+    column::reader::{get_typed_column_reader, ColumnReader},
+    data_type::{
+        BoolType, ByteArray, ByteArrayType, DataType, DoubleType, FloatType, Int32Type, Int64Type,
+    },
+    errors::ParquetError,
+    file::{
+        properties::WriterProperties,
+        reader::{FileReader, SerializedFileReader},
+        writer::{SerializedColumnWriter, SerializedFileWriter},
+    },
+    schema::types::{ColumnDescriptor, Type},
+};
+use std::{collections::HashSet, fs::File, sync::Arc};
+
+fn parquet_err(e: ParquetError) -> Error {
+    Error::new(e.to_string())
+}
+
+// Every column is written OPTIONAL so nulls round trip through definition levels
+fn field_type(col: &Col) -> Result<Type, Error> {
+    let (physical, logical) = match col.typed().zero() {
+        Cell::Int(_) => (
+            PhysicalType::INT64,
+            Some(LogicalType::Integer {
+                bit_width: 64,
+                is_signed: true,
+            }),
+        ),
+        Cell::Uint(_) => (
+            PhysicalType::INT64,
+            Some(LogicalType::Integer {
+                bit_width: 64,
+                is_signed: false,
+            }),
+        ),
+        Cell::Float(_) => (PhysicalType::DOUBLE, None),
+        Cell::Bool(_) => (PhysicalType::BOOLEAN, None),
+        Cell::Str(_) => (PhysicalType::BYTE_ARRAY, Some(LogicalType::String)),
+        _ => (
+            PhysicalType::INT64,
+            Some(LogicalType::Timestamp {
+                is_adjusted_to_u_t_c: false,
+                unit: TimeUnit::MICROS(Default::default()),
+            }),
+        ),
+    };
+    Type::primitive_type_builder(col.name(), physical)
+        .with_repetition(Repetition::OPTIONAL)
+        .with_logical_type(logical)
+        .build()
+        .map_err(parquet_err)
+}
+
+fn write_values<T: DataType>(
+    writer: &mut SerializedColumnWriter,
+    values: Vec<T::T>,
+    levels: &[i16],
+) -> Result<(), Error> {
+    match writer.typed::<T>().write_batch(&values, Some(levels), None) {
+        Ok(_) => Ok(()),
+        Err(e) => Err(parquet_err(e)),
+    }
+}
+
+fn write_col(writer: &mut SerializedColumnWriter, col: &Col) -> Result<(), Error> {
+    let levels = (0..col.len())
+        .map(|i| if col.is_null(i) { 0 } else { 1 })
+        .collect::<Vec<i16>>();
+    let cells = col.iter();
+    match col.typed().zero() {
+        Cell::Int(_) => write_values::<Int64Type>(
+            writer,
+            cells
+                .filter_map(|cell| match cell {
+                    Cell::Int(x) => Some(x),
+                    _ => None,
+                })
+                .collect(),
+            &levels,
+        ),
+        // Parquet has no unsigned physical type, the logical type marks the bits as unsigned
+        Cell::Uint(_) => write_values::<Int64Type>(
+            writer,
+            cells
+                .filter_map(|cell| match cell {
+                    Cell::Uint(x) => Some(x as i64),
+                    _ => None,
+                })
+                .collect(),
+            &levels,
+        ),
+        Cell::Float(_) => write_values::<DoubleType>(
+            writer,
+            cells
+                .filter_map(|cell| match cell {
+                    Cell::Float(x) => Some(x),
+                    _ => None,
+                })
+                .collect(),
+            &levels,
+        ),
+        Cell::Bool(_) => write_values::<BoolType>(
+            writer,
+            cells
+                .filter_map(|cell| match cell {
+                    Cell::Bool(x) => Some(x),
+                    _ => None,
+                })
+                .collect(),
+            &levels,
+        ),
+        Cell::Str(_) => write_values::<ByteArrayType>(
+            writer,
+            cells
+                .filter_map(|cell| match cell {
+                    Cell::Str(x) => Some(ByteArray::from(x.as_str())),
+                    _ => None,
+                })
+                .collect(),
+            &levels,
+        ),
+        _ => write_values::<Int64Type>(
+            writer,
+            cells
+                .filter_map(|cell| match cell {
+                    Cell::DateTime(x) => Some(x.and_utc().timestamp_micros()),
+                    _ => None,
+                })
+                .collect(),
+            &levels,
+        ),
+    }
+}
+
+pub(crate) fn write(file: File, cols: &[Col]) -> Result<(), Error> {
+    let fields = cols
+        .iter()
+        .map(|col| field_type(col).map(Arc::new))
+        .collect::<Result<Vec<_>, Error>>()?;
+    let schema = Type::group_type_builder("schema")
+        .with_fields(fields)
+        .build()
+        .map_err(parquet_err)?;
+    let props = WriterProperties::builder().build();
+    let mut writer =
+        SerializedFileWriter::new(file, Arc::new(schema), Arc::new(props)).map_err(parquet_err)?;
+    let mut row_group = writer.next_row_group().map_err(parquet_err)?;
+    for col in cols {
+        let mut col_writer = match row_group.next_column().map_err(parquet_err)? {
+            Some(col_writer) => col_writer,
+            None => return Err(Error::new("Parquet schema mismatch".to_string())),
+        };
+        write_col(&mut col_writer, col)?;
+        col_writer.close().map_err(parquet_err)?;
+    }
+    row_group.close().map_err(parquet_err)?;
+    writer.close().map_err(parquet_err)?;
+    Ok(())
+}
+
+fn unsupported(descr: &ColumnDescriptor) -> Error {
+    Error::new(format!(
+        "Unsupported Parquet type {} in column `{}`",
+        descr.physical_type(),
+        descr.name()
+    ))
+}
+
+fn is_unsigned(descr: &ColumnDescriptor) -> bool {
+    matches!(
+        descr.logical_type(),
+        Some(LogicalType::Integer {
+            is_signed: false,
+            ..
+        })
+    )
+}
+
+fn to_date_time(x: i64, unit: &TimeUnit) -> Option<NaiveDateTime> {
+    match unit {
+        TimeUnit::MILLIS(_) => DateTime::from_timestamp_millis(x),
+        TimeUnit::MICROS(_) => DateTime::from_timestamp_micros(x),
+        TimeUnit::NANOS(_) => Some(DateTime::from_timestamp_nanos(x)),
+    }
+    .map(|dt| dt.naive_utc())
+}
+
+fn out_of_range(descr: &ColumnDescriptor) -> Error {
+    Error::new(format!("Value out of range for column `{}`", descr.name()))
+}
+
+fn col_type(descr: &ColumnDescriptor) -> Result<Cell, Error> {
+    if descr.max_rep_level() > 0 || descr.path().parts().len() > 1 {
+        return Err(Error::new(format!(
+            "Nested Parquet column `{}` is not supported",
+            descr.path()
+        )));
+    }
+    Ok(match (descr.physical_type(), descr.logical_type()) {
+        (PhysicalType::BOOLEAN, _) => Cell::Bool(false),
+        (PhysicalType::INT64, Some(LogicalType::Timestamp { .. }))
+        | (PhysicalType::INT32, Some(LogicalType::Date)) => null_date().zero(),
+        (PhysicalType::INT32 | PhysicalType::INT64, _) if is_unsigned(descr) => Cell::Uint(0),
+        (PhysicalType::INT32 | PhysicalType::INT64, _) => Cell::Int(0),
+        (PhysicalType::FLOAT | PhysicalType::DOUBLE, _) => Cell::Float(0.0),
+        (PhysicalType::BYTE_ARRAY, _) => Cell::Str(String::new()),
+        _ => return Err(unsupported(descr)),
+    })
+}
+
+// Reads every record of one column chunk, filling nulls wherever the definition level
+// is below the column max
+fn read_values<T: DataType>(
+    reader: ColumnReader,
+    rows: usize,
+    descr: &ColumnDescriptor,
+    typed: &Cell,
+    to_cell: impl Fn(T::T) -> Option<Cell>,
+    out: &mut Vec<Cell>,
+) -> Result<(), Error> {
+    let mut reader = get_typed_column_reader::<T>(reader);
+    let mut levels = vec![];
+    let mut values = vec![];
+    reader
+        .read_records(rows, Some(&mut levels), None, &mut values)
+        .map_err(parquet_err)?;
+    let mut values = values.into_iter();
+    let mut next = || match values.next().and_then(&to_cell) {
+        Some(cell) => Ok(cell),
+        None => Err(out_of_range(descr)),
+    };
+    if descr.max_def_level() == 0 {
+        for _ in 0..rows {
+            out.push(next()?);
+        }
+        return Ok(());
+    }
+    for level in levels {
+        match level == descr.max_def_level() {
+            true => out.push(next()?),
+            false => out.push(typed.null()),
+        }
+    }
+    Ok(())
+}
+
+fn read_col(
+    reader: ColumnReader,
+    rows: usize,
+    descr: &ColumnDescriptor,
+    typed: &Cell,
+    out: &mut Vec<Cell>,
+) -> Result<(), Error> {
+    let unsigned = is_unsigned(descr);
+    match (descr.physical_type(), descr.logical_type()) {
+        (PhysicalType::BOOLEAN, _) => {
+            read_values::<BoolType>(reader, rows, descr, typed, |x| Some(Cell::Bool(x)), out)
+        }
+        (PhysicalType::INT32, Some(LogicalType::Date)) => read_values::<Int32Type>(
+            reader,
+            rows,
+            descr,
+            typed,
+            |x| {
+                // Days since the unix epoch
+                x.checked_add(719_163)
+                    .and_then(NaiveDate::from_num_days_from_ce_opt)
+                    .and_then(|date| date.and_hms_opt(0, 0, 0))
+                    .map(Cell::DateTime)
+            },
+            out,
+        ),
+        (PhysicalType::INT32, _) => read_values::<Int32Type>(
+            reader,
+            rows,
+            descr,
+            typed,
+            |x| match unsigned {
+                true => Some(Cell::Uint(x as u32 as u64)),
+                false => Some(Cell::Int(x as i64)),
+            },
+            out,
+        ),
+        (PhysicalType::INT64, Some(LogicalType::Timestamp { unit, .. })) => {
+            read_values::<Int64Type>(
+                reader,
+                rows,
+                descr,
+                typed,
+                |x| to_date_time(x, &unit).map(Cell::DateTime),
+                out,
+            )
+        }
+        (PhysicalType::INT64, _) => read_values::<Int64Type>(
+            reader,
+            rows,
+            descr,
+            typed,
+            |x| match unsigned {
+                true => Some(Cell::Uint(x as u64)),
+                false => Some(Cell::Int(x)),
+            },
+            out,
+        ),
+        (PhysicalType::FLOAT, _) => read_values::<FloatType>(
+            reader,
+            rows,
+            descr,
+            typed,
+            |x| Some(Cell::Float(x as f64)),
+            out,
+        ),
+        (PhysicalType::DOUBLE, _) => {
+            read_values::<DoubleType>(reader, rows, descr, typed, |x| Some(Cell::Float(x)), out)
+        }
+        (PhysicalType::BYTE_ARRAY, _) => read_values::<ByteArrayType>(
+            reader,
+            rows,
+            descr,
+            typed,
+            |x| x.as_utf8().ok().map(|s| Cell::Str(s.to_string())),
+            out,
+        ),
+        _ => Err(unsupported(descr)),
+    }
+}
+
+// `cols` limits which leaf columns are decoded, the rest of the file is never read
+pub(crate) fn read(file: File, cols: Option<HashSet<&str>>) -> Result<Vec<Col>, Error> {
+    let reader = SerializedFileReader::new(file).map_err(parquet_err)?;
+    let metadata = reader.metadata();
+    let schema = metadata.file_metadata().schema_descr();
+    if let Some(cols) = &cols {
+        if let Some(name) = cols
+            .iter()
+            .find(|name| !schema.columns().iter().any(|descr| descr.name() == **name))
+        {
+            return Err(Error::new(format!("Column `{name}` not found")));
+        }
+    }
+    let mut selected = vec![];
+    for (i, descr) in schema.columns().iter().enumerate() {
+        if let Some(cols) = &cols {
+            if !cols.contains(descr.name()) {
+                continue;
+            }
+        }
+        selected.push((i, descr.clone(), col_type(descr)?));
+    }
+    let total = metadata.file_metadata().num_rows() as usize;
+    let mut values: Vec<Vec<Cell>> = selected.iter().map(|_| Vec::with_capacity(total)).collect();
+    for rg in 0..metadata.num_row_groups() {
+        let row_group = reader.get_row_group(rg).map_err(parquet_err)?;
+        let rows = metadata.row_group(rg).num_rows() as usize;
+        for (j, (i, descr, typed)) in selected.iter().enumerate() {
+            let col_reader = row_group.get_column_reader(*i).map_err(parquet_err)?;
+            read_col(col_reader, rows, descr, typed, &mut values[j])?;
+        }
+    }
+    Ok(selected
+        .into_iter()
+        .zip(values)
+        .map(|((_, descr, typed), values)| Col::build(descr.name().to_string(), values, typed))
+        .collect())
+}
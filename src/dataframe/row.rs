@@ -1,10 +1,15 @@
-use crate::cell::*;
+use crate::{cell::*, util::Error};
+use std::collections::HashMap;
 
 pub trait ToRow {
     fn to_row(&self) -> Vec<Cell>;
     fn labels(&self) -> Vec<String>;
 }
 
+pub trait FromRow: Sized {
+    fn from_row(row: &HashMap<String, Cell>) -> Result<Self, Error>;
+}
+
 #[macro_export]
 macro_rules! row {
     ( $( $x:expr),* ) => {
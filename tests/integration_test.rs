@@ -1,6 +1,6 @@
 use rowboat::dataframe::*;
 use serde::Deserialize;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 mod example;
 
@@ -417,6 +417,531 @@ fn join_dataframe() {
     assert_eq!(result_df, expected_df);
 }
 
+#[test]
+fn join_types() {
+    let users = Dataframe::from_rows(
+        vec!["id", "name"],
+        vec![row!(4, "Sally"), row!(1, "Jasper"), row!(2, "Susie")],
+    )
+    .unwrap();
+    let snacks = Dataframe::from_rows(
+        vec!["id", "snack"],
+        vec![row!(1, "Apple"), row!(2, "Banana"), row!(9, "Kiwi")],
+    )
+    .unwrap();
+
+    let result_df = users
+        .join_with(&snacks, &[("id", "id")], JoinType::Right)
+        .unwrap();
+    let expected_df = Dataframe::from_rows(
+        vec!["id", "name", "snack"],
+        vec![
+            row!(1, "Jasper", "Apple"),
+            row!(2, "Susie", "Banana"),
+            row!(9, None::<String>, "Kiwi"),
+        ],
+    )
+    .unwrap();
+    assert_eq!(result_df, expected_df);
+
+    let result_df = users
+        .join_with(&snacks, &[("id", "id")], JoinType::Full)
+        .unwrap();
+    let expected_df = Dataframe::from_rows(
+        vec!["id", "name", "snack"],
+        vec![
+            row!(4, Some("Sally"), None::<String>),
+            row!(1, Some("Jasper"), Some("Apple")),
+            row!(2, Some("Susie"), Some("Banana")),
+            row!(9, None::<String>, Some("Kiwi")),
+        ],
+    )
+    .unwrap();
+    assert_eq!(result_df, expected_df);
+
+    // unmatched right rows keep their key when the key columns differ in type
+    let unsigned = Dataframe::from_rows(
+        vec!["id", "snack"],
+        vec![row!(2_u32, "Banana"), row!(9_u32, "Kiwi")],
+    )
+    .unwrap();
+    let result_df = users
+        .join_with(&unsigned, &[("id", "id")], JoinType::Right)
+        .unwrap();
+    let expected_df = Dataframe::from_rows(
+        vec!["id", "name", "snack"],
+        vec![
+            row!(2, Some("Susie"), "Banana"),
+            row!(9, None::<String>, "Kiwi"),
+        ],
+    )
+    .unwrap();
+    assert_eq!(result_df, expected_df);
+    let result_df = users
+        .join_with(&unsigned, &[("id", "id")], JoinType::Full)
+        .unwrap();
+    let expected_df = Dataframe::from_rows(
+        vec!["id", "name", "snack"],
+        vec![
+            row!(4, Some("Sally"), None::<String>),
+            row!(1, Some("Jasper"), None::<String>),
+            row!(2, Some("Susie"), Some("Banana")),
+            row!(9, None::<String>, Some("Kiwi")),
+        ],
+    )
+    .unwrap();
+    assert_eq!(result_df, expected_df);
+
+    let result_df = users
+        .join_with(&snacks, &[("id", "id")], JoinType::Semi)
+        .unwrap();
+    let expected_df = Dataframe::from_rows(
+        vec!["id", "name"],
+        vec![row!(1, "Jasper"), row!(2, "Susie")],
+    )
+    .unwrap();
+    assert_eq!(result_df, expected_df);
+
+    let result_df = users
+        .join_with(&snacks, &[("id", "id")], JoinType::Anti)
+        .unwrap();
+    let expected_df = Dataframe::from_rows(vec!["id", "name"], vec![row!(4, "Sally")]).unwrap();
+    assert_eq!(result_df, expected_df);
+
+    let sizes = Dataframe::from_rows(vec!["size"], vec![row!("S"), row!("L")]).unwrap();
+    let result_df = users
+        .join_with(&sizes, &[("id", "size")], JoinType::Cross)
+        .unwrap();
+    let expected_df = Dataframe::from_rows(
+        vec!["id", "name", "size"],
+        vec![
+            row!(4, "Sally", "S"),
+            row!(4, "Sally", "L"),
+            row!(1, "Jasper", "S"),
+            row!(1, "Jasper", "L"),
+            row!(2, "Susie", "S"),
+            row!(2, "Susie", "L"),
+        ],
+    )
+    .unwrap();
+    assert_eq!(result_df, expected_df);
+    let result_df = users.join_with(&snacks, &[], JoinType::Cross).unwrap();
+    assert_eq!(
+        result_df.col_names(),
+        vec!["id_left", "name", "id_right", "snack"]
+    );
+    assert_eq!(result_df.length(), 9);
+}
+
+#[test]
+fn join_suffixes() {
+    let df = generic_dataframe().slice(0, 2).unwrap().to_dataframe();
+    let other = Dataframe::from_rows(
+        vec!["id", "name", "score"],
+        vec![row!(4, "Sal", 1.5), row!(7, "Kim", 2.0)],
+    )
+    .unwrap();
+
+    let result_df = df.join(&other, "id", "id").unwrap();
+    let expected_df = Dataframe::from_rows(
+        vec![
+            "id",
+            "name_left",
+            "age",
+            "score_left",
+            "registered",
+            "name_right",
+            "score_right",
+        ],
+        vec![row!(4, "Sally", 23, 700, true, "Sal", 1.5)],
+    )
+    .unwrap();
+    assert_eq!(result_df, expected_df);
+
+    let result_df = df
+        .join_with_options(
+            &other,
+            &[("id", "id")],
+            JoinType::Full,
+            &JoinOptions::new().suffixes("", "_other").keep_keys(true),
+        )
+        .unwrap();
+    let expected_df = Dataframe::from_rows(
+        vec![
+            "id",
+            "name",
+            "age",
+            "score",
+            "registered",
+            "id_other",
+            "name_other",
+            "score_other",
+        ],
+        vec![
+            row!(
+                Some(4),
+                Some("Sally"),
+                Some(23),
+                Some(700),
+                Some(true),
+                Some(4),
+                Some("Sal"),
+                Some(1.5)
+            ),
+            row!(
+                Some(1),
+                Some("Jasper"),
+                Some(41),
+                Some(900),
+                Some(false),
+                None::<i64>,
+                None::<String>,
+                None::<f64>
+            ),
+            row!(
+                None::<i64>,
+                None::<String>,
+                None::<i64>,
+                None::<i64>,
+                None::<bool>,
+                Some(7),
+                Some("Kim"),
+                Some(2.0)
+            ),
+        ],
+    )
+    .unwrap();
+    assert_eq!(result_df, expected_df);
+}
+
+#[test]
+fn join_multi_key() {
+    let orders = Dataframe::from_rows(
+        vec!["customer_id", "region", "total"],
+        vec![
+            row!(1, "east", 10),
+            row!(1, "west", 20),
+            row!(2, "east", 30),
+            row!(3, "west", 40),
+        ],
+    )
+    .unwrap();
+    let managers = Dataframe::from_rows(
+        vec!["customer_id", "region", "manager"],
+        vec![
+            row!(1, "west", "Sally"),
+            row!(2, "east", "Jasper"),
+            row!(2, "west", "Jake"),
+        ],
+    )
+    .unwrap();
+
+    let result_df = orders
+        .join_with(
+            &managers,
+            &[("customer_id", "customer_id"), ("region", "region")],
+            JoinType::Left,
+        )
+        .unwrap();
+    let expected_df = Dataframe::from_rows(
+        vec!["customer_id", "region", "total", "manager"],
+        vec![
+            row!(1, "east", 10, None::<String>),
+            row!(1, "west", 20, Some("Sally")),
+            row!(2, "east", 30, Some("Jasper")),
+            row!(3, "west", 40, None::<String>),
+        ],
+    )
+    .unwrap();
+    assert_eq!(result_df, expected_df);
+
+    let mut renamed = managers.clone();
+    renamed.rename_col("customer_id", "cid").unwrap();
+    renamed.rename_col("region", "area").unwrap();
+    let result_df = orders
+        .join_with(
+            &renamed,
+            &[("customer_id", "cid"), ("region", "area")],
+            JoinType::Full,
+        )
+        .unwrap();
+    let expected_df = Dataframe::from_rows(
+        vec!["customer_id", "region", "total", "manager"],
+        vec![
+            row!(1, "east", Some(10), None::<String>),
+            row!(1, "west", Some(20), Some("Sally")),
+            row!(2, "east", Some(30), Some("Jasper")),
+            row!(3, "west", Some(40), None::<String>),
+            row!(2, "west", None::<i64>, Some("Jake")),
+        ],
+    )
+    .unwrap();
+    assert_eq!(result_df, expected_df);
+
+    // the region columns overlap when only the customer is a key
+    let result_df = orders
+        .join_with(
+            &managers,
+            &[("customer_id", "customer_id")],
+            JoinType::Inner,
+        )
+        .unwrap();
+    assert_eq!(
+        result_df.col_names(),
+        vec![
+            "customer_id",
+            "region_left",
+            "total",
+            "region_right",
+            "manager"
+        ]
+    );
+    match orders.join_with(&managers, &[], JoinType::Inner) {
+        Ok(_) => panic!("Join without keys not detected"),
+        Err(err) => assert_eq!(err.to_string(), "Join requires at least one key"),
+    }
+}
+
+#[test]
+fn join_key_types() {
+    let df = Dataframe::from_rows(
+        vec!["id", "name"],
+        vec![
+            row!(Some(1), "Sally"),
+            row!(Some(2), "Jasper"),
+            row!(None::<i64>, "Jake"),
+        ],
+    )
+    .unwrap();
+
+    // numbers match by value, strings never match numbers
+    let floats =
+        Dataframe::from_rows(vec!["fid", "score"], vec![row!(1.0, 10), row!(2.5, 20)]).unwrap();
+    let result_df = df.join(&floats, "id", "fid").unwrap();
+    let expected_df =
+        Dataframe::from_rows(vec!["id", "name", "score"], vec![row!(1, "Sally", 10)]).unwrap();
+    assert_eq!(result_df, expected_df);
+    let strs = Dataframe::from_rows(vec!["sid", "score"], vec![row!("1", 10)]).unwrap();
+    assert_eq!(df.join(&strs, "id", "sid").unwrap().length(), 0);
+
+    // right only keys take the left key type, or widen it when they don't fit
+    let floats = Dataframe::from_rows(
+        vec!["fid", "score"],
+        vec![row!(1.0, 10), row!(3.0, 30), row!(2.5, 20)],
+    )
+    .unwrap();
+    let result_df = df
+        .join_with(&floats, &[("id", "fid")], JoinType::Right)
+        .unwrap();
+    let expected_df = Dataframe::from_rows(
+        vec!["id", "name", "score"],
+        vec![
+            row!(1.0, Some("Sally"), 10),
+            row!(3.0, None::<String>, 30),
+            row!(2.5, None::<String>, 20),
+        ],
+    )
+    .unwrap();
+    assert_eq!(result_df, expected_df);
+    let whole =
+        Dataframe::from_rows(vec!["fid", "score"], vec![row!(1.0, 10), row!(3.0, 30)]).unwrap();
+    let result_df = df
+        .join_with(&whole, &[("id", "fid")], JoinType::Full)
+        .unwrap();
+    let expected_df = Dataframe::from_rows(
+        vec!["id", "name", "score"],
+        vec![
+            row!(Some(1), Some("Sally"), Some(10)),
+            row!(Some(2), Some("Jasper"), None::<i64>),
+            row!(None::<i64>, Some("Jake"), None::<i64>),
+            row!(Some(3), None::<String>, Some(30)),
+        ],
+    )
+    .unwrap();
+    assert_eq!(result_df, expected_df);
+
+    // null keys only match with `nulls_equal`
+    let other = Dataframe::from_rows(
+        vec!["id", "score"],
+        vec![row!(None::<i64>, 30), row!(Some(2), 20)],
+    )
+    .unwrap();
+    let result_df = df.join(&other, "id", "id").unwrap();
+    let expected_df =
+        Dataframe::from_rows(vec!["id", "name", "score"], vec![row!(2, "Jasper", 20)]).unwrap();
+    assert_eq!(result_df, expected_df);
+    let result_df = df
+        .join_with_options(
+            &other,
+            &[("id", "id")],
+            JoinType::Inner,
+            &JoinOptions::new().nulls_equal(true),
+        )
+        .unwrap();
+    let expected_df = Dataframe::from_rows(
+        vec!["id", "name", "score"],
+        vec![row!(Some(2), "Jasper", 20), row!(None::<i64>, "Jake", 30)],
+    )
+    .unwrap();
+    assert_eq!(result_df, expected_df);
+
+    // a null group is kept apart from the string "Null"
+    let df = Dataframe::from_rows(
+        vec!["tag"],
+        vec![row!(Some("Null")), row!(None::<String>), row!(Some("Null"))],
+    )
+    .unwrap();
+    let chunks = df.to_slice().chunk_by("tag").unwrap();
+    assert_eq!(
+        chunks.iter().map(|c| c.length()).collect::<Vec<usize>>(),
+        vec![2, 1]
+    );
+    assert_eq!(Cell::Float(f64::NAN), Cell::Float(f64::NAN));
+    assert_eq!(
+        Cell::Float(f64::NAN).partial_cmp(&Cell::Float(-f64::NAN)),
+        Some(std::cmp::Ordering::Equal)
+    );
+    assert!(Cell::Float(f64::NAN) > Cell::Float(f64::INFINITY));
+    assert_eq!(
+        Cell::Float(-0.0).partial_cmp(&Cell::Float(0.0)),
+        Some(std::cmp::Ordering::Equal)
+    );
+    assert!(Cell::Int(5) < Cell::Float(1.0));
+    assert_eq!(Cell::Float(-0.0), Cell::Float(0.0));
+    assert_ne!(Cell::Int(1), Cell::Float(1.0));
+}
+
+#[test]
+fn join_asof() {
+    let trades = Dataframe::from_rows(
+        vec!["at", "ticker", "qty"],
+        vec![
+            row!(Timestamp(2024, 8, 26, 9, 30, 5), "AAPL", 100),
+            row!(Timestamp(2024, 8, 26, 9, 30, 20), "MSFT", 50),
+            row!(Timestamp(2024, 8, 26, 9, 31, 0), "AAPL", 10),
+        ],
+    )
+    .unwrap();
+    let quotes = Dataframe::from_rows(
+        vec!["quoted", "symbol", "bid"],
+        vec![
+            row!(Timestamp(2024, 8, 26, 9, 30, 10), "MSFT", 410.5),
+            row!(Timestamp(2024, 8, 26, 9, 30, 0), "AAPL", 220.0),
+            row!(Timestamp(2024, 8, 26, 9, 30, 30), "AAPL", 220.5),
+        ],
+    )
+    .unwrap();
+
+    let result_df = trades
+        .join_asof_with(
+            &quotes,
+            "at",
+            "quoted",
+            AsofDirection::Backward,
+            &AsofOptions::new().by("ticker", "symbol"),
+        )
+        .unwrap();
+    let expected_df = Dataframe::from_rows(
+        vec!["at", "ticker", "qty", "quoted", "bid"],
+        vec![
+            row!(
+                Timestamp(2024, 8, 26, 9, 30, 5),
+                "AAPL",
+                100,
+                Timestamp(2024, 8, 26, 9, 30, 0),
+                220.0
+            ),
+            row!(
+                Timestamp(2024, 8, 26, 9, 30, 20),
+                "MSFT",
+                50,
+                Timestamp(2024, 8, 26, 9, 30, 10),
+                410.5
+            ),
+            row!(
+                Timestamp(2024, 8, 26, 9, 31, 0),
+                "AAPL",
+                10,
+                Timestamp(2024, 8, 26, 9, 30, 30),
+                220.5
+            ),
+        ],
+    )
+    .unwrap();
+    assert_eq!(result_df, expected_df);
+
+    // forward within 20 seconds, ignoring the ticker
+    let result_df = trades
+        .join_asof_with(
+            &quotes,
+            "at",
+            "quoted",
+            AsofDirection::Forward,
+            &AsofOptions::new().tolerance(20.0),
+        )
+        .unwrap();
+    assert_eq!(
+        result_df.col_values("bid").unwrap(),
+        &vec![410.5.to_cell(), 220.5.to_cell(), null_float()]
+    );
+
+    let left = Dataframe::from_rows(vec!["x"], vec![row!(1), row!(4), row!(6), row!(9)]).unwrap();
+    let right = Dataframe::from_rows(
+        vec!["x", "label"],
+        vec![row!(2.0, "two"), row!(7.0, "seven")],
+    )
+    .unwrap();
+    let result_df = left
+        .join_asof(&right, "x", "x", AsofDirection::Nearest)
+        .unwrap();
+    // the left key keeps its name when both keys share it
+    let expected_df = Dataframe::from_rows(
+        vec!["x", "label"],
+        vec![
+            row!(1, "two"),
+            row!(4, "two"),
+            row!(6, "seven"),
+            row!(9, "seven"),
+        ],
+    )
+    .unwrap();
+    assert_eq!(result_df, expected_df);
+
+    // nanosecond epoch keys are compared exactly
+    let left = Dataframe::from_rows(vec!["ts"], vec![row!(1_700_000_000_000_000_100_i64)]).unwrap();
+    let right = Dataframe::from_rows(
+        vec!["quoted", "bid"],
+        vec![
+            row!(1_700_000_000_000_000_050_i64, 1.0),
+            row!(1_700_000_000_000_000_150_i64, 2.0),
+        ],
+    )
+    .unwrap();
+    for (direction, bid) in [
+        (AsofDirection::Forward, 2.0),
+        (AsofDirection::Backward, 1.0),
+        (AsofDirection::Nearest, 1.0),
+    ] {
+        let result_df = left.join_asof(&right, "ts", "quoted", direction).unwrap();
+        assert_eq!(result_df.col_values("bid").unwrap(), &vec![bid.to_cell()]);
+    }
+
+    match trades.join_asof(&quotes, "ticker", "symbol", AsofDirection::Backward) {
+        Ok(_) => panic!("Non numeric as-of key not detected"),
+        Err(err) => assert_eq!(
+            err.to_string(),
+            "As-of join key `ticker` must be numeric or DateTime"
+        ),
+    }
+    match trades.join_asof(&quotes, "at", "bid", AsofDirection::Backward) {
+        Ok(_) => panic!("Mismatched as-of keys not detected"),
+        Err(err) => assert_eq!(
+            err.to_string(),
+            "As-of join keys must both be numeric or both DateTime"
+        ),
+    }
+}
+
 #[test]
 fn sort_dataframe() {
     let mut df = generic_dataframe();
@@ -514,28 +1039,128 @@ fn opt_dataframe() {
     assert_eq!(df, expected_df);
 }
 
-#[derive(Deserialize, ToRow)]
-struct MyRow {
-    name: String,
-    age: i64,
-    val: bool,
-}
-
 #[test]
-fn from_structs() {
-    let df = Dataframe::from_structs(vec![
-        MyRow {
-            name: "Jake".to_string(),
-            age: 23,
-            val: true,
-        },
-        MyRow {
-            name: "Sally".to_string(),
-            age: 44,
-            val: false,
-        },
-        MyRow {
-            name: "Jasper".to_string(),
+fn columnar() {
+    let mut col = Col::new(
+        "name".to_string(),
+        vec![Some("Sasha"), None, Some("Jane"), Some("Jerry")],
+    );
+    assert_eq!(col.len(), 4);
+    assert!(col.is_null(1));
+    assert_eq!(col.get(1), Some(null_str()));
+    assert_eq!(col.get(4), None);
+
+    col.set(1, "Alex".to_cell()).unwrap();
+    col.set(2, None::<String>.to_cell()).unwrap();
+    col.push("Kim".to_cell()).unwrap();
+    assert_eq!(
+        col.values(),
+        &vec![
+            "Sasha".to_cell(),
+            "Alex".to_cell(),
+            null_str(),
+            "Jerry".to_cell(),
+            "Kim".to_cell()
+        ]
+    );
+    match col.push(1.to_cell()) {
+        Ok(_) => panic!("Should not push mismatched cell"),
+        Err(err) => assert_eq!(err.to_string(), "Invalid cell type"),
+    }
+    match col.set(5, "Lee".to_cell()) {
+        Ok(_) => panic!("Should not set out of bounds"),
+        Err(err) => assert_eq!(err.to_string(), "Index out of bounds"),
+    }
+
+    let picked = col.gather(&[4, 2, 0]);
+    assert_eq!(
+        picked,
+        Col::new("name".to_string(), vec![Some("Kim"), None, Some("Sasha")])
+    );
+
+    let mut flags = Col::new("flag".to_string(), vec![true, false]);
+    flags
+        .extend(&Col::new("flag".to_string(), vec![None, Some(true)]))
+        .unwrap();
+    assert_eq!(
+        flags.iter().collect::<Vec<Cell>>(),
+        vec![true.to_cell(), false.to_cell(), null_bool(), true.to_cell()]
+    );
+    match flags.extend(&col) {
+        Ok(_) => panic!("Should not extend with mismatched column"),
+        Err(err) => assert_eq!(err.to_string(), "Invalid cell type"),
+    }
+
+    // overwritten strings read back whether they shrink or grow
+    let mut words = Col::new("word".to_string(), vec!["alpha", "beta", "gamma"]);
+    for (i, word) in ["a", "", "epsilon", "b", "longer than before"]
+        .iter()
+        .enumerate()
+    {
+        words.set(i % 3, word.to_cell()).unwrap();
+    }
+    assert_eq!(
+        words,
+        Col::new(
+            "word".to_string(),
+            vec!["b", "longer than before", "epsilon"]
+        )
+    );
+
+    // borrowed cell view follows writes
+    assert_eq!(words.coalesce(), Some(&"b".to_cell()));
+    words.values_mut()[0] = None::<String>.to_cell();
+    words.values_mut().push("zeta".to_cell());
+    assert!(words.is_null(0));
+    assert_eq!(words.coalesce(), Some(&"longer than before".to_cell()));
+    assert_eq!(words.len(), 4);
+    let slice = ColSlice::new("word", &words.values()[1..3], words.typed());
+    assert_eq!(slice.to_col(), words.gather(&[1, 2]));
+
+    // borrowed cells past the first chunk of rows, refreshed after writes
+    let mut big = Dataframe::new(None);
+    big.add_col("n", (0..2500).collect::<Vec<i64>>()).unwrap();
+    assert_eq!(big.cell(2100, "n"), Some(&2100.to_cell()));
+    assert_eq!(big.cell(2500, "n"), None);
+    big.set_val(2100, "n", -1).unwrap();
+    assert_eq!(big.cell(2100, "n"), Some(&(-1).to_cell()));
+    assert_eq!(big.iter().nth(2499).unwrap()["n"], &2499.to_cell());
+
+    // values of another type become nulls instead of changing the column type
+    let mut nums = Col::build(
+        "num".to_string(),
+        vec![1.to_cell(), 2.5.to_cell(), null_int()],
+        Cell::Int(0),
+    );
+    assert_eq!(nums, Col::new("num".to_string(), vec![Some(1), None, None]));
+    nums.values_mut().push("x".to_cell());
+    assert_eq!(nums.typed(), &Cell::Int(0));
+    assert_eq!(nums.get(0), Some(1.to_cell()));
+    assert_eq!(nums.get(3), Some(null_int()));
+}
+
+#[derive(Deserialize, ToRow, FromRow, Debug, PartialEq)]
+struct MyRow {
+    name: String,
+    age: i64,
+    val: bool,
+}
+
+#[test]
+fn from_structs() {
+    let df = Dataframe::from_structs(vec![
+        MyRow {
+            name: "Jake".to_string(),
+            age: 23,
+            val: true,
+        },
+        MyRow {
+            name: "Sally".to_string(),
+            age: 44,
+            val: false,
+        },
+        MyRow {
+            name: "Jasper".to_string(),
             age: 61,
             val: true,
         },
@@ -553,538 +1178,1964 @@ fn from_structs() {
     assert_eq!(df, expected_df);
 }
 
+#[derive(FromRow, Debug, PartialEq)]
+struct ScoreRow {
+    id: u32,
+    score: Option<f64>,
+    at: Option<chrono::NaiveDateTime>,
+}
+
 #[test]
-fn csv_dataframe() {
-    let df = Dataframe::from_csv::<MyRow>("./tests/test.csv").unwrap();
-    let expected_df = Dataframe::from_rows(
+fn to_structs() {
+    let rows = vec![
+        MyRow {
+            name: "Jake".to_string(),
+            age: 23,
+            val: true,
+        },
+        MyRow {
+            name: "Sally".to_string(),
+            age: 44,
+            val: false,
+        },
+    ];
+    let df = Dataframe::from_rows(
         vec!["name", "age", "val"],
+        vec![row!("Jake", 23, true), row!("Sally", 44, false)],
+    )
+    .unwrap();
+    assert_eq!(df.to_structs::<MyRow>().unwrap(), rows);
+
+    let df = Dataframe::from_rows(
+        vec!["id", "score", "at", "extra"],
         vec![
-            row!("Jake", 23, true),
-            row!("Sally", 44, false),
-            row!("Jasper", 61, true),
+            row!(1, Some(2), Timestamp(2024, 8, 26, 12, 15, 0), "x"),
+            row!(2, None::<i64>, null_date(), "y"),
         ],
     )
     .unwrap();
-    assert_eq!(df, expected_df);
-
-    df.to_csv("./tests/new_test.csv").unwrap();
-}
-
-#[test]
-fn iterrows() {
-    let df = dataframe_extension();
-    df.iter().enumerate().for_each(|(i, row)| match i {
-        0 => {
-            let mut map: HashMap<String, &Cell> = HashMap::new();
-            let name = "Sasha".to_cell();
-            map.insert(String::from("id"), &Cell::Int(6));
-            map.insert(String::from("name"), &name);
-            map.insert(String::from("age"), &Cell::Int(33));
-            map.insert(String::from("score"), &Cell::Int(1600));
-            map.insert(String::from("registered"), &Cell::Bool(false));
-            assert_eq!(row, map);
-        }
-        1 => {
-            let mut map: HashMap<String, &Cell> = HashMap::new();
-            let name = "Jane".to_cell();
-            map.insert(String::from("id"), &Cell::Int(7));
-            map.insert(String::from("name"), &name);
-            map.insert(String::from("age"), &Cell::Int(24));
-            map.insert(String::from("score"), &Cell::Int(700));
-            map.insert(String::from("registered"), &Cell::Bool(true));
-            assert_eq!(row, map);
-        }
-        2 => {
-            let mut map: HashMap<String, &Cell> = HashMap::new();
-            let name = "Jerry".to_cell();
-            map.insert(String::from("id"), &Cell::Int(8));
-            map.insert(String::from("name"), &name);
-            map.insert(String::from("age"), &Cell::Int(39));
-            map.insert(String::from("score"), &Cell::Int(400));
-            map.insert(String::from("registered"), &Cell::Bool(true));
-        }
-        _ => {
-            panic!("dataframe iter index out of bounds.")
-        }
-    });
-
-    df.into_iter().enumerate().for_each(|(i, row)| match i {
-        0 => {
-            let mut map: HashMap<String, Cell> = HashMap::new();
-            map.insert(String::from("id"), Cell::Int(6));
-            map.insert(String::from("name"), "Sasha".to_cell());
-            map.insert(String::from("age"), Cell::Int(33));
-            map.insert(String::from("score"), Cell::Int(1600));
-            map.insert(String::from("registered"), Cell::Bool(false));
-            assert_eq!(row, map);
-        }
-        1 => {
-            let mut map: HashMap<String, Cell> = HashMap::new();
-            map.insert(String::from("id"), Cell::Int(7));
-            map.insert(String::from("name"), "Jane".to_cell());
-            map.insert(String::from("age"), Cell::Int(24));
-            map.insert(String::from("score"), Cell::Int(700));
-            map.insert(String::from("registered"), Cell::Bool(true));
-            assert_eq!(row, map);
-        }
-        2 => {
-            let mut map: HashMap<String, Cell> = HashMap::new();
-            map.insert(String::from("id"), Cell::Int(8));
-            map.insert(String::from("name"), "Jerry".to_cell());
-            map.insert(String::from("age"), Cell::Int(39));
-            map.insert(String::from("score"), Cell::Int(400));
-            map.insert(String::from("registered"), Cell::Bool(true));
-        }
-        _ => {
-            panic!("dataframe iter index out of bounds.")
-        }
-    });
-
-    // chunk
     assert_eq!(
-        generic_dataframe()
-            .iter_chunk(2)
-            .map(|df| df)
-            .collect::<Vec<Dataframe>>(),
+        df.to_structs::<ScoreRow>().unwrap(),
         vec![
-            Dataframe::from_rows(
-                vec!["id", "name", "age", "score", "registered"],
-                vec![
-                    row!(4, "Sally", 23, 700, true),
-                    row!(1, "Jasper", 41, 900, false),
-                ],
-            )
-            .unwrap(),
-            Dataframe::from_rows(
-                vec!["id", "name", "age", "score", "registered"],
-                vec![
-                    row!(5, "Jake", 33, 1200, true),
-                    row!(2, "Susie", 27, 200, true),
-                ],
-            )
-            .unwrap(),
-            Dataframe::from_rows(
-                vec!["id", "name", "age", "score", "registered"],
-                vec![row!(3, "Spruce", 24, 800, false),],
-            )
-            .unwrap()
+            ScoreRow {
+                id: 1,
+                score: Some(2.0),
+                at: Some(
+                    chrono::NaiveDate::from_ymd_opt(2024, 8, 26)
+                        .unwrap()
+                        .and_hms_opt(12, 15, 0)
+                        .unwrap()
+                ),
+            },
+            ScoreRow {
+                id: 2,
+                score: None,
+                at: None,
+            },
         ]
-    )
-}
+    );
 
-#[test]
-fn errors() {
-    // shape
-    let mut df = generic_dataframe();
-    let result = df.add_col("new", vec![1, 2]);
-    match result {
-        Ok(_) => panic!("Shape err not detected"),
-        Err(err) => assert_eq!(err.to_string(), "Invalid col length".to_string()),
-    }
-    let result = df.add_col("id", vec![1, 2, 3, 4, 5]);
-    match result {
-        Ok(_) => panic!("Unique col err not detected"),
-        Err(err) => assert_eq!(err.to_string(), "Col names must be unique".to_string()),
-    }
-    let result = df.add_row(row![1, "Sally"]);
-    match result {
-        Ok(_) => panic!("Shape err not detected"),
-        Err(err) => assert_eq!(err.to_string(), "Invalid row length".to_string()),
-    }
-    match Dataframe::from_rows(
+    let df = Dataframe::from_rows(
         vec!["name", "age", "val"],
-        vec![row!("Jake", 23, true), row!("Sally", 44)],
-    ) {
-        Ok(_) => panic!("Shape err not detected"),
-        Err(err) => assert_eq!(err.to_string(), "Inconsistent data shape".to_string()),
-    }
-    let mut df = generic_dataframe();
-    match df.concat(alt_dataframe()) {
-        Ok(_) => panic!("Concat shape err not detected"),
+        vec![row!("Jake", 23, true), row!("Sally", None::<i64>, false)],
+    )
+    .unwrap();
+    match df.to_structs::<MyRow>() {
+        Ok(_) => panic!("Null err not detected"),
         Err(err) => assert_eq!(
             err.to_string(),
-            "Concat against mismatched dataframes".to_string()
+            "Row 1: Column `age`: Cannot convert Null(Int) to i64".to_string()
         ),
     }
-    match df.join(&generic_dataframe(), "id", "id") {
-        Ok(_) => panic!("Join unique err not detected"),
+    let df = Dataframe::from_rows(vec!["id", "score"], vec![row!(-1, 2.0)]).unwrap();
+    match df.to_structs::<ScoreRow>() {
+        Ok(_) => panic!("Range err not detected"),
         Err(err) => assert_eq!(
             err.to_string(),
-            "Join dataframe columns are not unique".to_string()
+            "Row 0: Column `id`: Value -1 out of range for u32".to_string()
         ),
     }
-    match df.column("unknown") {
-        Ok(_) => panic!("Missing col err not detected"),
-        Err(err) => assert_eq!(err.to_string(), "Column not found".to_string()),
+    let df = Dataframe::from_rows(vec!["id", "score"], vec![row!(1, 2.0)]).unwrap();
+    match df.to_structs::<ScoreRow>() {
+        Ok(_) => panic!("Missing column err not detected"),
+        Err(err) => assert_eq!(err.to_string(), "Row 0: Missing column `at`".to_string()),
     }
-    match df.col_mut("unknown") {
-        Ok(_) => panic!("Missing col err not detected"),
-        Err(err) => assert_eq!(err.to_string(), "Column not found".to_string()),
+}
+
+mod cents {
+    use rowboat::dataframe::*;
+    pub fn to_cell(val: &f64) -> Cell {
+        Cell::Int((val * 100.0).round() as i64)
+    }
+    pub fn from_cell(cell: &Cell) -> Result<f64, Error> {
+        Ok(i64::from_cell(cell)? as f64 / 100.0)
     }
 }
 
-#[test]
-fn drop() {
-    let mut df = generic_dataframe();
-    df.drop_cols(["name", "registered"].into());
-    let expected_df = Dataframe::from_rows(
-        vec!["id", "age", "score"],
-        vec![
-            row!(4, 23, 700),
-            row!(1, 41, 900),
-            row!(5, 33, 1200),
-            row!(2, 27, 200),
-            row!(3, 24, 800),
-        ],
-    )
-    .unwrap();
-    assert_eq!(df, expected_df);
+#[derive(ToRow, FromRow, Debug, PartialEq)]
+struct Address {
+    #[rowboat(rename = "address_city")]
+    city: String,
+    zip: u32,
+}
 
-    let mut df = generic_dataframe();
-    let expected_df = Dataframe::from_rows(
-        vec!["name", "registered"],
-        vec![
-            row!("Sally", true),
-            row!("Jasper", false),
-            row!("Jake", true),
-            row!("Susie", true),
-            row!("Spruce", false),
-        ],
-    )
-    .unwrap();
-    df.retain_cols(["name", "registered"].into());
-    assert_eq!(df, expected_df);
+#[derive(ToRow, FromRow, Debug, PartialEq)]
+struct Customer {
+    #[rowboat(rename = "customer_id")]
+    id: i64,
+    #[rowboat(skip)]
+    session: Option<String>,
+    #[rowboat(flatten)]
+    address: Address,
+    #[rowboat(with = cents)]
+    balance: f64,
 }
 
+#[derive(ToRow, FromRow, Debug, PartialEq)]
+struct Pair(String, i64);
+
 #[test]
-fn describe() {
-    let es: Vec<u32> = vec![];
-    let ds = Col::new("val".to_string(), es.clone()).describe();
+fn row_attributes() {
+    let customers = vec![Customer {
+        id: 1,
+        session: Some("abc".to_string()),
+        address: Address {
+            city: "Denver".to_string(),
+            zip: 80202,
+        },
+        balance: 12.5,
+    }];
+    let df = Dataframe::from_structs(customers).unwrap();
     assert_eq!(
-        ds,
+        df,
+        Dataframe::from_rows(
+            vec!["customer_id", "address_city", "zip", "balance"],
+            vec![row!(1, "Denver", 80202_u32, 1250)],
+        )
+        .unwrap()
+    );
+    assert_eq!(
+        df.to_structs::<Customer>().unwrap(),
+        vec![Customer {
+            id: 1,
+            session: None,
+            address: Address {
+                city: "Denver".to_string(),
+                zip: 80202,
+            },
+            balance: 12.5,
+        }]
+    );
+
+    let df = Dataframe::from_structs(vec![Pair("a".to_string(), 1)]).unwrap();
+    assert_eq!(
+        df,
+        Dataframe::from_rows(vec!["0", "1"], vec![row!("a", 1)]).unwrap()
+    );
+    assert_eq!(
+        df.to_structs::<Pair>().unwrap(),
+        vec![Pair("a".to_string(), 1)]
+    );
+}
+
+#[test]
+fn derive_errors() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
+
+#[test]
+fn csv_dataframe() {
+    let df = Dataframe::from_csv::<MyRow>("./tests/test.csv").unwrap();
+    let expected_df = Dataframe::from_rows(
+        vec!["name", "age", "val"],
+        vec![
+            row!("Jake", 23, true),
+            row!("Sally", 44, false),
+            row!("Jasper", 61, true),
+        ],
+    )
+    .unwrap();
+    assert_eq!(df, expected_df);
+
+    df.to_csv("./tests/new_test.csv").unwrap();
+}
+
+#[test]
+fn read_csv_dataframe() {
+    let df = Dataframe::read_csv("./tests/infer.csv", &CsvOptions::new()).unwrap();
+    let expected_df = Dataframe::from_rows(
+        vec!["id", "name", "score", "active", "joined", "note"],
+        vec![
+            row!(
+                1,
+                Some("Sally"),
+                Some(23.0),
+                true,
+                Timestamp(2024, 8, 26, 12, 15, 0),
+                None::<String>
+            ),
+            row!(
+                2,
+                Some("Jasper"),
+                Some(41.5),
+                false,
+                Timestamp(2024, 8, 27, 0, 0, 0),
+                Some("hello")
+            ),
+            row!(
+                3,
+                None::<String>,
+                None::<f64>,
+                true,
+                null_date(),
+                Some("world")
+            ),
+        ],
+    )
+    .unwrap();
+    assert_eq!(df, expected_df);
+
+    // type override
+    let df = Dataframe::read_csv(
+        "./tests/infer.csv",
+        &CsvOptions::new().col_type("id", Cell::Uint(0)),
+    )
+    .unwrap();
+    assert_eq!(
+        df.col_values("id").unwrap(),
+        &vec![Cell::Uint(1), Cell::Uint(2), Cell::Uint(3)]
+    );
+
+    // value outside of the inference sample
+    match Dataframe::read_csv("./tests/infer.csv", &CsvOptions::new().infer_rows(1)) {
+        Ok(_) => panic!("Parse err not detected"),
+        Err(err) => assert_eq!(
+            err.to_string(),
+            "Could not parse `41.5` as Int in column `score` row 1".to_string()
+        ),
+    }
+}
+
+#[test]
+fn csv_options() {
+    let df = Dataframe::from_rows(
+        vec!["name", "score", "at", "active"],
+        vec![
+            row!(
+                "Sally\tSmith",
+                Some(23.0),
+                Timestamp(2024, 8, 26, 12, 15, 0),
+                Some(true)
+            ),
+            row!(
+                "Jasper",
+                None::<f64>,
+                Timestamp(2024, 8, 27, 8, 5, 0),
+                None::<bool>
+            ),
+            row!(
+                "",
+                Some(41.25),
+                Timestamp(2024, 8, 28, 22, 45, 0),
+                Some(false)
+            ),
+        ],
+    )
+    .unwrap();
+    let options = CsvOptions::new()
+        .delimiter(b'\t')
+        .quote(b'\'')
+        .null_token("NA")
+        .datetime_format("%d/%m/%Y %H:%M")
+        .float_precision(2);
+    let path = std::env::temp_dir().join("rowboat_csv_options.tsv");
+    let path = path.to_str().unwrap();
+    df.to_csv_with(path, &options).unwrap();
+    assert_eq!(
+        std::fs::read_to_string(path).unwrap(),
+        "name\tscore\tat\tactive\n'Sally\tSmith'\t23.00\t26/08/2024 12:15\ttrue\nJasper\tNA\t27/08/2024 08:05\tNA\n\t41.25\t28/08/2024 22:45\tfalse\n"
+    );
+    assert_eq!(Dataframe::read_csv(path, &options).unwrap(), df);
+
+    // headerless struct round trip
+    let options = CsvOptions::new().delimiter(b';').has_header(false);
+    let df = Dataframe::from_csv::<MyRow>("./tests/test.csv").unwrap();
+    let path = std::env::temp_dir().join("rowboat_csv_options.csv");
+    let path = path.to_str().unwrap();
+    df.to_csv_with(path, &options).unwrap();
+    assert_eq!(
+        std::fs::read_to_string(path).unwrap(),
+        "Jake;23;true\nSally;44;false\nJasper;61;true\n"
+    );
+    assert_eq!(
+        Dataframe::from_csv_with::<MyRow>(path, &options).unwrap(),
+        df
+    );
+}
+
+#[test]
+fn csv_round_trip() {
+    let df = Dataframe::from_rows(
+        vec!["name", "score"],
+        vec![
+            row!(Some(""), Some(f64::NAN)),
+            row!(None::<&str>, Some(f64::INFINITY)),
+            row!(Some("Jasper"), Some(f64::NEG_INFINITY)),
+            row!(Some("NA"), None::<f64>),
+        ],
+    )
+    .unwrap();
+    // empty strings need a null token of their own
+    let options = CsvOptions::new().null_token("NULL");
+    let mut buf: Vec<u8> = vec![];
+    df.write_csv(&mut buf, &options).unwrap();
+    assert_eq!(
+        String::from_utf8(buf.clone()).unwrap(),
+        "name,score\n,NaN\nNULL,inf\nJasper,-inf\nNA,NULL\n"
+    );
+    assert_eq!(
+        Dataframe::read_csv_reader(buf.as_slice(), &options).unwrap(),
+        df
+    );
+
+    // by default empty fields are nulls
+    let mut buf: Vec<u8> = vec![];
+    df.write_csv(&mut buf, &CsvOptions::new()).unwrap();
+    let back = Dataframe::read_csv_reader(buf.as_slice(), &CsvOptions::new()).unwrap();
+    assert_eq!(back.cell(0, "name"), Some(&null_str()));
+    assert_eq!(back.cell(1, "score"), Some(&f64::INFINITY.to_cell()));
+
+    // empty fields are only null strings when they match the null token
+    let df =
+        Dataframe::read_csv("./tests/infer.csv", &CsvOptions::new().null_token("NULL")).unwrap();
+    assert_eq!(
+        df.col_values("note").unwrap(),
+        &vec![
+            Cell::Str("".to_string()),
+            Cell::Str("hello".to_string()),
+            Cell::Str("world".to_string())
+        ]
+    );
+    assert_eq!(df.cell(2, "score"), Some(&null_float()));
+
+    // words are not floats
+    let df =
+        Dataframe::read_csv_reader("a\nnan\ninfinity\n".as_bytes(), &CsvOptions::new()).unwrap();
+    assert_eq!(df.col_types(), vec!["a <Str>"]);
+}
+
+#[test]
+fn csv_io() {
+    let body = "name,age,val\nJake,23,true\nSally,44,false\nJasper,61,true\n";
+    let expected_df = Dataframe::from_csv::<MyRow>("./tests/test.csv").unwrap();
+    let df = Dataframe::from_csv_reader::<MyRow, _>(body.as_bytes(), &CsvOptions::new()).unwrap();
+    assert_eq!(df, expected_df);
+    let df = Dataframe::read_csv_reader(body.as_bytes(), &CsvOptions::new()).unwrap();
+    assert_eq!(df, expected_df);
+
+    let mut buf: Vec<u8> = vec![];
+    df.write_csv(&mut buf, &CsvOptions::new()).unwrap();
+    assert_eq!(String::from_utf8(buf).unwrap(), body);
+
+    let mut buf: Vec<u8> = vec![];
+    df.slice(1, 2)
+        .unwrap()
+        .write_csv(&mut buf, &CsvOptions::new().delimiter(b'|'))
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(buf).unwrap(),
+        "name|age|val\nSally|44|false\n"
+    );
+}
+
+#[test]
+fn scan_csv() {
+    let expected_df = Dataframe::read_csv("./tests/infer.csv", &CsvOptions::new()).unwrap();
+    let chunks = Dataframe::scan_csv("./tests/infer.csv", 2)
+        .unwrap()
+        .collect::<Result<Vec<Dataframe>, Error>>()
+        .unwrap();
+    assert_eq!(chunks.len(), 2);
+    assert_eq!(chunks[0], expected_df.slice(0, 2).unwrap().to_dataframe());
+    assert_eq!(chunks[1], expected_df.slice(2, 3).unwrap().to_dataframe());
+
+    // schema sampled once and kept for later chunks
+    let mut df = Dataframe::new(None);
+    Dataframe::scan_csv_with("./tests/infer.csv", 1, &CsvOptions::new().infer_rows(2))
+        .unwrap()
+        .for_each(|chunk| match df.length() {
+            0 => df = chunk.unwrap(),
+            _ => df.concat(chunk.unwrap()).unwrap(),
+        });
+    assert_eq!(df, expected_df);
+
+    let mut scan =
+        Dataframe::scan_csv_with("./tests/infer.csv", 1, &CsvOptions::new().infer_rows(1)).unwrap();
+    assert!(scan.next().unwrap().is_ok());
+    match scan.next().unwrap() {
+        Ok(_) => panic!("Parse err not detected"),
+        Err(err) => assert_eq!(
+            err.to_string(),
+            "Could not parse `41.5` as Int in column `score` row 1".to_string()
+        ),
+    }
+    assert!(scan.next().is_none());
+}
+
+#[test]
+fn json() {
+    let df = Dataframe::from_rows(
+        vec!["id", "name", "score", "active"],
+        vec![
+            row!(1, "Sally", Some(23.5), true),
+            row!(2, "Jasper", None::<f64>, false),
+        ],
+    )
+    .unwrap();
+    let records = r#"[{"id":1,"name":"Sally","score":23.5,"active":true},{"id":2,"name":"Jasper","score":null,"active":false}]"#;
+    let columns =
+        r#"{"id":[1,2],"name":["Sally","Jasper"],"score":[23.5,null],"active":[true,false]}"#;
+    let ndjson = "{\"id\":1,\"name\":\"Sally\",\"score\":23.5,\"active\":true}\n{\"id\":2,\"name\":\"Jasper\",\"score\":null,\"active\":false}\n";
+    assert_eq!(df.to_json(JsonOrient::Records), records);
+    assert_eq!(df.to_json(JsonOrient::Columns), columns);
+    assert_eq!(df.to_ndjson(), ndjson);
+    assert_eq!(Dataframe::from_json(records).unwrap(), df);
+    assert_eq!(Dataframe::from_json(columns).unwrap(), df);
+    assert_eq!(Dataframe::from_ndjson(ndjson).unwrap(), df);
+
+    // missing keys are null and mixed numbers widen to float
+    let df = Dataframe::from_json(r#"[{"a":1,"b":"x"},{"a":2.5},{"b":"z","a":3}]"#).unwrap();
+    assert_eq!(
+        df,
+        Dataframe::from_rows(
+            vec!["a", "b"],
+            vec![
+                row!(1.0, Some("x")),
+                row!(2.5, None::<String>),
+                row!(3.0, Some("z")),
+            ],
+        )
+        .unwrap()
+    );
+
+    match Dataframe::from_json(r#"{"a":[1,"one"]}"#) {
+        Ok(_) => panic!("Heterogeneous column err not detected"),
+        Err(err) => assert_eq!(
+            err.to_string(),
+            "Heterogeneous column `a`: Int and Str".to_string()
+        ),
+    }
+    match Dataframe::from_json(r#"{"a":[1,2],"b":[true]}"#) {
+        Ok(_) => panic!("Shape err not detected"),
+        Err(err) => assert_eq!(err.to_string(), "Inconsistent data shape".to_string()),
+    }
+    match Dataframe::from_ndjson("{\"a\":1}\n{\"a\":}\n") {
+        Ok(_) => panic!("Invalid line not detected"),
+        Err(err) => assert!(err.to_string().starts_with("Invalid JSON on line 2:")),
+    }
+
+    // timestamps read back as DateTime, other strings stay Str
+    let df = Dataframe::from_rows(
+        vec!["at", "label"],
+        vec![
+            row!(Timestamp(2024, 8, 26, 12, 15, 0), "2024-08-26"),
+            row!(null_date(), "2024-08-27 08:05:30"),
+        ],
+    )
+    .unwrap();
+    assert_eq!(
+        df.to_ndjson(),
+        "{\"at\":\"2024-08-26 12:15:00\",\"label\":\"2024-08-26\"}\n{\"at\":null,\"label\":\"2024-08-27 08:05:30\"}\n"
+    );
+    assert_eq!(Dataframe::from_ndjson(&df.to_ndjson()).unwrap(), df);
+    assert_eq!(
+        Dataframe::from_json(&df.to_json(JsonOrient::Columns)).unwrap(),
+        df
+    );
+}
+
+#[cfg(feature = "parquet")]
+#[test]
+fn parquet() {
+    let df = Dataframe::from_rows(
+        vec!["id", "count", "name", "score", "active", "at"],
+        vec![
+            row!(
+                -1,
+                Some(u64::MAX),
+                "Sally",
+                Some(23.5),
+                true,
+                Timestamp(2024, 8, 26, 12, 15, 0)
+            ),
+            row!(
+                2,
+                None::<u64>,
+                "Jasper",
+                None::<f64>,
+                false,
+                Timestamp(2024, 8, 27, 8, 5, 30)
+            ),
+        ],
+    )
+    .unwrap();
+    let path = std::env::temp_dir().join("rowboat_test.parquet");
+    let path = path.to_str().unwrap();
+    df.write_parquet(path).unwrap();
+    assert_eq!(Dataframe::read_parquet(path).unwrap(), df);
+    assert_eq!(
+        Dataframe::read_parquet_cols(path, std::collections::HashSet::from(["name", "id"]))
+            .unwrap(),
+        Dataframe::from_rows(
+            vec!["id", "name"],
+            vec![row!(-1, "Sally"), row!(2, "Jasper")]
+        )
+        .unwrap()
+    );
+    match Dataframe::read_parquet_cols(path, std::collections::HashSet::from(["name", "ids"])) {
+        Ok(_) => panic!("Unknown parquet col not detected"),
+        Err(err) => assert_eq!(err.to_string(), "Column `ids` not found"),
+    }
+
+    // Date32 days past what chrono can represent
+    use parquet::{
+        data_type::Int32Type,
+        file::{properties::WriterProperties, writer::SerializedFileWriter},
+        schema::parser::parse_message_type,
+    };
+    let schema = parse_message_type("message schema { REQUIRED INT32 day (DATE); }").unwrap();
+    let path = std::env::temp_dir().join("rowboat_date32.parquet");
+    let path = path.to_str().unwrap();
+    let mut writer = SerializedFileWriter::new(
+        std::fs::File::create(path).unwrap(),
+        std::sync::Arc::new(schema),
+        std::sync::Arc::new(WriterProperties::builder().build()),
+    )
+    .unwrap();
+    let mut row_group = writer.next_row_group().unwrap();
+    let mut col = row_group.next_column().unwrap().unwrap();
+    col.typed::<Int32Type>()
+        .write_batch(&[0, i32::MAX], None, None)
+        .unwrap();
+    col.close().unwrap();
+    row_group.close().unwrap();
+    writer.close().unwrap();
+    match Dataframe::read_parquet(path) {
+        Ok(_) => panic!("Date32 overflow not detected"),
+        Err(err) => assert_eq!(err.to_string(), "Value out of range for column `day`"),
+    }
+}
+
+#[cfg(feature = "arrow")]
+#[test]
+fn arrow() {
+    use arrow_array::Array;
+    let df = Dataframe::from_rows(
+        vec!["id", "count", "name", "score", "active", "at"],
+        vec![
+            row!(
+                -1,
+                Some(u64::MAX),
+                "Sally",
+                Some(23.5),
+                true,
+                Timestamp(2024, 8, 26, 12, 15, 0)
+            ),
+            row!(
+                2,
+                None::<u64>,
+                "Jasper",
+                None::<f64>,
+                false,
+                Timestamp(2024, 8, 27, 8, 5, 30)
+            ),
+        ],
+    )
+    .unwrap();
+    let batch = df.to_record_batch().unwrap();
+    assert_eq!(batch.num_rows(), 2);
+    assert_eq!(batch.column(1).null_count(), 1);
+    assert_eq!(batch.column(4).null_count(), 0);
+    assert_eq!(Dataframe::from_record_batch(&batch).unwrap(), df);
+
+    let path = std::env::temp_dir().join("rowboat_test.arrow");
+    let path = path.to_str().unwrap();
+    df.write_ipc(path).unwrap();
+    assert_eq!(Dataframe::read_ipc(path).unwrap(), df);
+}
+
+#[test]
+fn serde() {
+    let df = Dataframe::from_rows(
+        vec!["id", "count", "name", "score", "active", "at"],
+        vec![
+            row!(
+                -1,
+                Some(7_u64),
+                "Sally",
+                Some(23.5),
+                Some(true),
+                Timestamp(2024, 8, 26, 12, 15, 0)
+            ),
+            row!(
+                2,
+                None::<u64>,
+                "Jasper",
+                None::<f64>,
+                None::<bool>,
+                null_date()
+            ),
+        ],
+    )
+    .unwrap();
+    let json = serde_json::to_string(&df).unwrap();
+    let back: Dataframe = serde_json::from_str(&json).unwrap();
+    assert_eq!(back, df);
+    assert_eq!(
+        back.col_names(),
+        vec!["id", "count", "name", "score", "active", "at"]
+    );
+    assert_eq!(back.cell(1, "count"), Some(&null_uint()));
+    assert_eq!(
+        serde_json::to_string(&df.column("count").unwrap()).unwrap(),
+        r#"{"name":"count","values":[{"Uint":7},{"Null":{"Uint":0}}],"typed":{"Uint":0}}"#
+    );
+
+    match serde_json::from_str::<Col>(
+        r#"{"name":"a","values":[{"Int":1},{"Str":"x"}],"typed":{"Int":0}}"#,
+    ) {
+        Ok(_) => panic!("Invalid cell type err not detected"),
+        Err(err) => assert_eq!(
+            err.to_string(),
+            "Invalid cell type in column `a`".to_string()
+        ),
+    }
+}
+
+#[test]
+fn iterrows() {
+    let df = dataframe_extension();
+    df.iter().enumerate().for_each(|(i, row)| match i {
+        0 => {
+            let mut map: HashMap<String, &Cell> = HashMap::new();
+            let name = "Sasha".to_cell();
+            map.insert(String::from("id"), &Cell::Int(6));
+            map.insert(String::from("name"), &name);
+            map.insert(String::from("age"), &Cell::Int(33));
+            map.insert(String::from("score"), &Cell::Int(1600));
+            map.insert(String::from("registered"), &Cell::Bool(false));
+            assert_eq!(row, map);
+        }
+        1 => {
+            let mut map: HashMap<String, &Cell> = HashMap::new();
+            let name = "Jane".to_cell();
+            map.insert(String::from("id"), &Cell::Int(7));
+            map.insert(String::from("name"), &name);
+            map.insert(String::from("age"), &Cell::Int(24));
+            map.insert(String::from("score"), &Cell::Int(700));
+            map.insert(String::from("registered"), &Cell::Bool(true));
+            assert_eq!(row, map);
+        }
+        2 => {
+            let mut map: HashMap<String, &Cell> = HashMap::new();
+            let name = "Jerry".to_cell();
+            map.insert(String::from("id"), &Cell::Int(8));
+            map.insert(String::from("name"), &name);
+            map.insert(String::from("age"), &Cell::Int(39));
+            map.insert(String::from("score"), &Cell::Int(400));
+            map.insert(String::from("registered"), &Cell::Bool(true));
+        }
+        _ => {
+            panic!("dataframe iter index out of bounds.")
+        }
+    });
+
+    df.into_iter().enumerate().for_each(|(i, row)| match i {
+        0 => {
+            let mut map: HashMap<String, Cell> = HashMap::new();
+            map.insert(String::from("id"), Cell::Int(6));
+            map.insert(String::from("name"), "Sasha".to_cell());
+            map.insert(String::from("age"), Cell::Int(33));
+            map.insert(String::from("score"), Cell::Int(1600));
+            map.insert(String::from("registered"), Cell::Bool(false));
+            assert_eq!(row, map);
+        }
+        1 => {
+            let mut map: HashMap<String, Cell> = HashMap::new();
+            map.insert(String::from("id"), Cell::Int(7));
+            map.insert(String::from("name"), "Jane".to_cell());
+            map.insert(String::from("age"), Cell::Int(24));
+            map.insert(String::from("score"), Cell::Int(700));
+            map.insert(String::from("registered"), Cell::Bool(true));
+            assert_eq!(row, map);
+        }
+        2 => {
+            let mut map: HashMap<String, Cell> = HashMap::new();
+            map.insert(String::from("id"), Cell::Int(8));
+            map.insert(String::from("name"), "Jerry".to_cell());
+            map.insert(String::from("age"), Cell::Int(39));
+            map.insert(String::from("score"), Cell::Int(400));
+            map.insert(String::from("registered"), Cell::Bool(true));
+        }
+        _ => {
+            panic!("dataframe iter index out of bounds.")
+        }
+    });
+
+    // chunk
+    assert_eq!(
+        generic_dataframe()
+            .iter_chunk(2)
+            .map(|df| df)
+            .collect::<Vec<Dataframe>>(),
+        vec![
+            Dataframe::from_rows(
+                vec!["id", "name", "age", "score", "registered"],
+                vec![
+                    row!(4, "Sally", 23, 700, true),
+                    row!(1, "Jasper", 41, 900, false),
+                ],
+            )
+            .unwrap(),
+            Dataframe::from_rows(
+                vec!["id", "name", "age", "score", "registered"],
+                vec![
+                    row!(5, "Jake", 33, 1200, true),
+                    row!(2, "Susie", 27, 200, true),
+                ],
+            )
+            .unwrap(),
+            Dataframe::from_rows(
+                vec!["id", "name", "age", "score", "registered"],
+                vec![row!(3, "Spruce", 24, 800, false),],
+            )
+            .unwrap()
+        ]
+    )
+}
+
+#[test]
+fn errors() {
+    // shape
+    let mut df = generic_dataframe();
+    let result = df.add_col("new", vec![1, 2]);
+    match result {
+        Ok(_) => panic!("Shape err not detected"),
+        Err(err) => assert_eq!(err.to_string(), "Invalid col length".to_string()),
+    }
+    let result = df.add_col("id", vec![1, 2, 3, 4, 5]);
+    match result {
+        Ok(_) => panic!("Unique col err not detected"),
+        Err(err) => assert_eq!(err.to_string(), "Col names must be unique".to_string()),
+    }
+    let result = df.add_row(row![1, "Sally"]);
+    match result {
+        Ok(_) => panic!("Shape err not detected"),
+        Err(err) => assert_eq!(err.to_string(), "Invalid row length".to_string()),
+    }
+    match Dataframe::from_rows(
+        vec!["name", "age", "val"],
+        vec![row!("Jake", 23, true), row!("Sally", 44)],
+    ) {
+        Ok(_) => panic!("Shape err not detected"),
+        Err(err) => assert_eq!(err.to_string(), "Inconsistent data shape".to_string()),
+    }
+    let mut df = generic_dataframe();
+    match df.concat(alt_dataframe()) {
+        Ok(_) => panic!("Concat shape err not detected"),
+        Err(err) => assert_eq!(
+            err.to_string(),
+            "Concat against mismatched dataframes".to_string()
+        ),
+    }
+    // `name` is suffixed into the existing `name_left`
+    let suffixed =
+        Dataframe::from_rows(vec!["id", "name", "name_left"], vec![row!(1, "Jas", "J")]).unwrap();
+    match suffixed.join(&generic_dataframe(), "id", "id") {
+        Ok(_) => panic!("Join unique err not detected"),
+        Err(err) => assert_eq!(
+            err.to_string(),
+            "Join dataframe columns are not unique".to_string()
+        ),
+    }
+    match df.column("unknown") {
+        Ok(_) => panic!("Missing col err not detected"),
+        Err(err) => assert_eq!(err.to_string(), "Column not found".to_string()),
+    }
+    match df.col_mut("unknown") {
+        Ok(_) => panic!("Missing col err not detected"),
+        Err(err) => assert_eq!(err.to_string(), "Column not found".to_string()),
+    }
+}
+
+#[test]
+fn drop() {
+    let mut df = generic_dataframe();
+    df.drop_cols(["name", "registered"].into());
+    let expected_df = Dataframe::from_rows(
+        vec!["id", "age", "score"],
+        vec![
+            row!(4, 23, 700),
+            row!(1, 41, 900),
+            row!(5, 33, 1200),
+            row!(2, 27, 200),
+            row!(3, 24, 800),
+        ],
+    )
+    .unwrap();
+    assert_eq!(df, expected_df);
+
+    let mut df = generic_dataframe();
+    let expected_df = Dataframe::from_rows(
+        vec!["name", "registered"],
+        vec![
+            row!("Sally", true),
+            row!("Jasper", false),
+            row!("Jake", true),
+            row!("Susie", true),
+            row!("Spruce", false),
+        ],
+    )
+    .unwrap();
+    df.retain_cols(["name", "registered"].into());
+    assert_eq!(df, expected_df);
+}
+
+#[test]
+fn describe() {
+    let es: Vec<u32> = vec![];
+    let ds = Col::new("val".to_string(), es.clone()).describe();
+    assert_eq!(
+        ds,
+        Dataframe::from_rows(
+            vec!["::", "val"],
+            vec![
+                row!("count", 0.0),
+                row!("mean", None::<f64>),
+                row!("std", None::<f64>),
+                row!("min", None::<f64>),
+                row!("25%", None::<f64>),
+                row!("50%", None::<f64>),
+                row!("75%", None::<f64>),
+                row!("max", None::<f64>),
+                row!("unique", None::<f64>),
+                row!("top idx", None::<f64>),
+                row!("freq", None::<f64>),
+            ],
+        )
+        .unwrap(),
+    );
+    assert_eq!(
+        Col::new("val".to_string(), vec![1]).describe(),
+        Dataframe::from_rows(
+            vec!["::", "val"],
+            vec![
+                row!("count", 1.0),
+                row!("mean", 1.0),
+                row!("std", None::<f64>),
+                row!("min", 1.0),
+                row!("25%", None::<f64>),
+                row!("50%", None::<f64>),
+                row!("75%", None::<f64>),
+                row!("max", 1.0),
+                row!("unique", None::<f64>),
+                row!("top idx", None::<f64>),
+                row!("freq", None::<f64>),
+            ],
+        )
+        .unwrap()
+    );
+    assert_eq!(
+        Col::new("val".to_string(), (1..=2).collect::<Vec<u32>>()).describe(),
+        Dataframe::from_rows(
+            vec!["::", "val"],
+            vec![
+                row!("count", 2.0),
+                row!("mean", 1.5),
+                row!("std", 0.5),
+                row!("min", 1.0),
+                row!("25%", None::<f64>),
+                row!("50%", None::<f64>),
+                row!("75%", None::<f64>),
+                row!("max", 2.0),
+                row!("unique", None::<f64>),
+                row!("top idx", None::<f64>),
+                row!("freq", None::<f64>),
+            ],
+        )
+        .unwrap()
+    );
+    assert_eq!(
+        Col::new("val".to_string(), (1..=4).collect::<Vec<u32>>()).describe(),
+        Dataframe::from_rows(
+            vec!["::", "val"],
+            vec![
+                row!("count", 4.0),
+                row!("mean", 2.5),
+                row!("std", 1.12),
+                row!("min", 1.0),
+                row!("25%", 1.5),
+                row!("50%", 2.5),
+                row!("75%", 3.5),
+                row!("max", 4.0),
+                row!("unique", None::<f64>),
+                row!("top idx", None::<f64>),
+                row!("freq", None::<f64>),
+            ],
+        )
+        .unwrap()
+    );
+    assert_eq!(
+        Col::new("val".to_string(), (1..=5).collect::<Vec<u32>>()).describe(),
+        Dataframe::from_rows(
+            vec!["::", "val"],
+            vec![
+                row!("count", 5.0),
+                row!("mean", 3.0),
+                row!("std", 1.41),
+                row!("min", 1.0),
+                row!("25%", 1.5),
+                row!("50%", 3.0),
+                row!("75%", 4.5),
+                row!("max", 5.0),
+                row!("unique", None::<f64>),
+                row!("top idx", None::<f64>),
+                row!("freq", None::<f64>),
+            ],
+        )
+        .unwrap()
+    );
+    assert_eq!(
+        Col::new("val".to_string(), (1..=6).collect::<Vec<u32>>()).describe(),
+        Dataframe::from_rows(
+            vec!["::", "val"],
+            vec![
+                row!("count", 6.0),
+                row!("mean", 3.5),
+                row!("std", 1.71),
+                row!("min", 1.0),
+                row!("25%", 2.0),
+                row!("50%", 3.5),
+                row!("75%", 5.0),
+                row!("max", 6.0),
+                row!("unique", None::<f64>),
+                row!("top idx", None::<f64>),
+                row!("freq", None::<f64>),
+            ],
+        )
+        .unwrap()
+    );
+    assert_eq!(
+        Col::new("val".to_string(), (1..=7).collect::<Vec<u32>>()).describe(),
+        Dataframe::from_rows(
+            vec!["::", "val"],
+            vec![
+                row!("count", 7.0),
+                row!("mean", 4.0),
+                row!("std", 2.0),
+                row!("min", 1.0),
+                row!("25%", 2.0),
+                row!("50%", 4.0),
+                row!("75%", 6.0),
+                row!("max", 7.0),
+                row!("unique", None::<f64>),
+                row!("top idx", None::<f64>),
+                row!("freq", None::<f64>),
+            ],
+        )
+        .unwrap()
+    );
+    assert_eq!(
+        Col::new("val".to_string(), (1..=8).collect::<Vec<u32>>()).describe(),
+        Dataframe::from_rows(
+            vec!["::", "val"],
+            vec![
+                row!("count", 8.0),
+                row!("mean", 4.5),
+                row!("std", 2.29),
+                row!("min", 1.0),
+                row!("25%", 2.5),
+                row!("50%", 4.5),
+                row!("75%", 6.5),
+                row!("max", 8.0),
+                row!("unique", None::<f64>),
+                row!("top idx", None::<f64>),
+                row!("freq", None::<f64>),
+            ],
+        )
+        .unwrap()
+    );
+    assert_eq!(
+        Col::new("val".to_string(), (1..=9).collect::<Vec<u32>>()).describe(),
+        Dataframe::from_rows(
+            vec!["::", "val"],
+            vec![
+                row!("count", 9.0),
+                row!("mean", 5.0),
+                row!("std", 2.58),
+                row!("min", 1.0),
+                row!("25%", 2.5),
+                row!("50%", 5.0),
+                row!("75%", 7.5),
+                row!("max", 9.0),
+                row!("unique", None::<f64>),
+                row!("top idx", None::<f64>),
+                row!("freq", None::<f64>),
+            ],
+        )
+        .unwrap()
+    );
+    assert_eq!(
+        Col::new("val".to_string(), (1..=10).collect::<Vec<u32>>()).describe(),
+        Dataframe::from_rows(
+            vec!["::", "val"],
+            vec![
+                row!("count", 10.0),
+                row!("mean", 5.5),
+                row!("std", 2.87),
+                row!("min", 1.0),
+                row!("25%", 3.0),
+                row!("50%", 5.5),
+                row!("75%", 8.0),
+                row!("max", 10.0),
+                row!("unique", None::<f64>),
+                row!("top idx", None::<f64>),
+                row!("freq", None::<f64>),
+            ],
+        )
+        .unwrap()
+    );
+    assert_eq!(
+        Col::new("val".to_string(), vec!["A", "B", "C", "B", "C", "B", "Z"]).describe(),
+        Dataframe::from_rows(
+            vec!["::", "val"],
+            vec![
+                row!("count", 7.0),
+                row!("mean", None::<f64>),
+                row!("std", None::<f64>),
+                row!("min", None::<f64>),
+                row!("25%", None::<f64>),
+                row!("50%", None::<f64>),
+                row!("75%", None::<f64>),
+                row!("max", None::<f64>),
+                row!("unique", 4.0),
+                row!("top idx", 1.0),
+                row!("freq", 3.0),
+            ],
+        )
+        .unwrap()
+    );
+}
+
+#[test]
+fn group() {
+    let df = Dataframe::from_rows(
+        vec!["name", "department", "salary", "age"],
+        vec![
+            row!("Jasper", "Sales", 100, 29),
+            row!("James", "Marketing", 200, 44),
+            row!("Susan", "Sales", 300, 65),
+            row!("Jane", "Marketing", 400, 47),
+            row!("Sam", "Sales", 100, 55),
+            row!("Sally", "Engineering", 200, 30),
+        ],
+    )
+    .unwrap();
+    df.to_slice()
+        .chunk_by("department")
+        .unwrap()
+        .into_iter()
+        .enumerate()
+        .for_each(|(i, chunk)| match i {
+            0 => assert_eq!(
+                chunk,
+                Dataframe::from_rows(
+                    vec!["name", "department", "salary", "age"],
+                    vec![
+                        row!("Jasper", "Sales", 100, 29),
+                        row!("Susan", "Sales", 300, 65),
+                        row!("Sam", "Sales", 100, 55),
+                    ],
+                )
+                .unwrap()
+            ),
+            1 => assert_eq!(
+                chunk,
+                Dataframe::from_rows(
+                    vec!["name", "department", "salary", "age"],
+                    vec![
+                        row!("James", "Marketing", 200, 44),
+                        row!("Jane", "Marketing", 400, 47),
+                    ],
+                )
+                .unwrap()
+            ),
+            _ => assert_eq!(
+                chunk,
+                Dataframe::from_rows(
+                    vec!["name", "department", "salary", "age"],
+                    vec![row!("Sally", "Engineering", 200, 30),],
+                )
+                .unwrap()
+            ),
+        });
+    let grouped = df
+        .group_by("department")
+        .select("department", Coalesce, "department")
+        .select("name", Count, "count")
+        .select("salary", Max, "max salary")
+        .select("salary", Min, "min salary")
+        .select("age", Mean, "average age")
+        .to_dataframe()
+        .unwrap();
+    assert_eq!(
+        grouped,
+        Dataframe::from_rows(
+            vec![
+                "department",
+                "count",
+                "max salary",
+                "min salary",
+                "average age"
+            ],
+            vec![
+                row!("Sales", 3 as u32, 300, 100, 49.67),
+                row!("Marketing", 2 as u32, 400, 200, 45.5),
+                row!("Engineering", 1 as u32, 200, 200, 30.0),
+            ],
+        )
+        .unwrap()
+    )
+}
+
+#[derive(Deserialize)]
+struct SneakyType {
+    name: String,
+    age: i64,
+    val: bool,
+}
+impl ToRow for SneakyType {
+    fn to_row(&self) -> Vec<Cell> {
+        if self.age < 40 {
+            vec![self.name.as_str().into(), self.age.into(), self.val.into()]
+        } else {
+            vec![
+                self.name.as_str().into(),
+                self.age.into(),
+                format!("{}", self.val).into(),
+            ]
+        }
+    }
+    fn labels(&self) -> Vec<String> {
+        vec!["name".to_string(), "age".to_string(), "val".to_string()]
+    }
+}
+#[test]
+fn group_multi() {
+    let df = Dataframe::from_rows(
+        vec!["department", "year", "salary"],
+        vec![
+            row!("Sales", Some(2023_u32), 100),
+            row!("Marketing", Some(2023_u32), 200),
+            row!("Sales", Some(2024_u32), 300),
+            row!("Sales", Some(2023_u32), 400),
+            row!("Marketing", None::<u32>, 100),
+        ],
+    )
+    .unwrap();
+    let grouped = df
+        .group_by_cols(vec!["department", "year"])
+        .select("salary", Count, "count")
+        .select("salary", Sum, "total")
+        .to_dataframe()
+        .unwrap();
+    assert_eq!(
+        grouped,
         Dataframe::from_rows(
-            vec!["::", "val"],
+            vec!["department", "year", "count", "total"],
             vec![
-                row!("count", 0.0),
-                row!("mean", None::<f64>),
-                row!("std", None::<f64>),
-                row!("min", None::<f64>),
-                row!("25%", None::<f64>),
-                row!("50%", None::<f64>),
-                row!("75%", None::<f64>),
-                row!("max", None::<f64>),
-                row!("unique", None::<f64>),
-                row!("top idx", None::<f64>),
-                row!("freq", None::<f64>),
+                row!("Sales", Some(2023_u32), 2_u32, 500),
+                row!("Marketing", Some(2023_u32), 1_u32, 200),
+                row!("Sales", Some(2024_u32), 1_u32, 300),
+                row!("Marketing", None::<u32>, 1_u32, 100),
             ],
         )
-        .unwrap(),
+        .unwrap()
     );
+    assert_eq!(grouped.col_types()[1], "year <Uint>");
+
+    match df
+        .group_by_cols(vec!["department", "region"])
+        .to_dataframe()
+    {
+        Ok(_) => panic!("Missing group col not detected"),
+        Err(err) => assert_eq!(err.to_string(), "Group by col not found"),
+    }
+}
+
+#[test]
+fn group_stats() {
+    let df = Dataframe::from_rows(
+        vec!["department", "salary"],
+        vec![
+            row!("Sales", 100),
+            row!("Marketing", 200),
+            row!("Sales", 300),
+            row!("Sales", 400),
+            row!("Marketing", 100),
+            row!("Sales", 200),
+            row!("Marketing", 200),
+            row!("HR", None::<i32>),
+        ],
+    )
+    .unwrap();
+    let grouped = df
+        .group_by("department")
+        .select("department", Coalesce, "department")
+        .select("salary", Median, "median")
+        .select("salary", Std, "std")
+        .select("salary", Var, "var")
+        .select("salary", Quantile(0.25), "p25")
+        .select("salary", First, "first")
+        .select("salary", Last, "last")
+        .select("salary", Mode, "mode")
+        .to_dataframe()
+        .unwrap();
     assert_eq!(
-        Col::new("val".to_string(), vec![1]).describe(),
+        grouped,
         Dataframe::from_rows(
-            vec!["::", "val"],
             vec![
-                row!("count", 1.0),
-                row!("mean", 1.0),
-                row!("std", None::<f64>),
-                row!("min", 1.0),
-                row!("25%", None::<f64>),
-                row!("50%", None::<f64>),
-                row!("75%", None::<f64>),
-                row!("max", 1.0),
-                row!("unique", None::<f64>),
-                row!("top idx", None::<f64>),
-                row!("freq", None::<f64>),
+                "department",
+                "median",
+                "std",
+                "var",
+                "p25",
+                "first",
+                "last",
+                "mode"
+            ],
+            vec![
+                row!(
+                    "Sales",
+                    250.0,
+                    12500.0_f64.sqrt(),
+                    12500.0,
+                    175.0,
+                    100,
+                    200,
+                    100
+                ),
+                row!(
+                    "Marketing",
+                    200.0,
+                    (20000.0_f64 / 9.0).sqrt(),
+                    20000.0 / 9.0,
+                    150.0,
+                    200,
+                    200,
+                    200
+                ),
+                row!(
+                    "HR",
+                    None::<f64>,
+                    None::<f64>,
+                    None::<f64>,
+                    None::<f64>,
+                    None::<i32>,
+                    None::<i32>,
+                    None::<i32>
+                ),
             ],
         )
         .unwrap()
     );
+
+    // Group stats agree with describe, which rounds to 2 decimals
+    let described = Col::new("salary".to_string(), vec![100, 300, 400, 200]).describe();
+    assert_eq!(described.cell(2, "salary"), Some(&Cell::Float(111.8)));
+    assert_eq!(described.cell(5, "salary"), Some(&Cell::Float(250.0)));
+
+    // Small scale spreads aren't rounded away
+    let small = Col::new("x".to_string(), vec![0.001, 0.003]);
+    assert!((small.std().unwrap() - 0.001).abs() < 1e-12);
+    assert!((small.var().unwrap() - 0.000001).abs() < 1e-15);
+
+    // Reducers can key maps and sets
+    let reducers = HashSet::from([Quantile(0.25), Median, Quantile(0.25), Quantile(0.5)]);
+    assert_eq!(reducers.len(), 3);
+}
+
+#[test]
+fn group_nulls() {
+    let df = Dataframe::from_rows(
+        vec!["team", "points", "games", "player"],
+        vec![
+            row!("A", Some(3), 2_u32, Some("Sam")),
+            row!("B", None::<i32>, 1_u32, None::<&str>),
+            row!("A", None::<i32>, 4_u32, Some("Jane")),
+            row!("A", Some(-5), 1_u32, None::<&str>),
+            row!("B", None::<i32>, 2_u32, None::<&str>),
+        ],
+    )
+    .unwrap();
+    let grouped = df
+        .group_by_cols(vec!["team"])
+        .select("points", Sum, "sum")
+        .select("points", Prod, "prod")
+        .select("points", Mean, "mean")
+        .select("points", Min, "min")
+        .select("points", Max, "max")
+        .select("points", NonNull, "non null")
+        .select("points", Coalesce, "first")
+        .select("games", Sum, "games")
+        .select("player", Min, "min player")
+        .select("player", Max, "max player")
+        .select("player", Unique, "players")
+        .to_dataframe()
+        .unwrap();
     assert_eq!(
-        Col::new("val".to_string(), (1..=2).collect::<Vec<u32>>()).describe(),
+        grouped,
         Dataframe::from_rows(
-            vec!["::", "val"],
             vec![
-                row!("count", 2.0),
-                row!("mean", 1.5),
-                row!("std", 0.5),
-                row!("min", 1.0),
-                row!("25%", None::<f64>),
-                row!("50%", None::<f64>),
-                row!("75%", None::<f64>),
-                row!("max", 2.0),
-                row!("unique", None::<f64>),
-                row!("top idx", None::<f64>),
-                row!("freq", None::<f64>),
+                "team",
+                "sum",
+                "prod",
+                "mean",
+                "min",
+                "max",
+                "non null",
+                "first",
+                "games",
+                "min player",
+                "max player",
+                "players"
+            ],
+            vec![
+                row!(
+                    "A",
+                    Some(-2),
+                    Some(-15),
+                    Some(-1.0),
+                    Some(-5),
+                    Some(3),
+                    2_u32,
+                    Some(3),
+                    7_u32,
+                    Some("Jane"),
+                    Some("Sam"),
+                    2_u32
+                ),
+                row!(
+                    "B",
+                    None::<i32>,
+                    None::<i32>,
+                    None::<f64>,
+                    None::<i32>,
+                    None::<i32>,
+                    0_u32,
+                    None::<i32>,
+                    3_u32,
+                    None::<&str>,
+                    None::<&str>,
+                    0_u32
+                ),
+            ],
+        )
+        .unwrap()
+    );
+
+    // integer overflow is an error rather than a null
+    let df = Dataframe::from_rows(
+        vec!["team", "points"],
+        vec![row!("A", i64::MAX), row!("A", 2), row!("B", 1)],
+    )
+    .unwrap();
+    let sum = df.group_by("team").select("points", Sum, "total");
+    let prod =
+        df.group_by("team")
+            .select("points", Max, "max")
+            .having(exp("prod(points)", gt(), 0));
+    for grouped in [sum, prod] {
+        match grouped.to_dataframe() {
+            Ok(_) => panic!("Integer overflow not detected"),
+            Err(err) => assert_eq!(err.to_string(), "Integer overflow reducing column `points`"),
+        }
+    }
+}
+
+#[test]
+fn group_agg() {
+    let df = Dataframe::from_rows(
+        vec!["department", "name", "salary", "hours"],
+        vec![
+            row!("Sales", "Jake", 100.0, 10.0),
+            row!("Marketing", "Susie", 200.0, 20.0),
+            row!("Sales", "Sally", 400.0, 30.0),
+        ],
+    )
+    .unwrap();
+    let grouped = df
+        .group_by("department")
+        .select("department", Coalesce, "department")
+        .agg("name", "names", |col| {
+            col.iter()
+                .map(|cell| cell.as_string())
+                .collect::<Vec<String>>()
+                .join(", ")
+                .to_cell()
+        })
+        .agg_cols(&["salary", "hours"], "weighted", |cols| {
+            let total = cols[0]
+                .iter()
+                .zip(cols[1].iter())
+                .map(|(salary, hours)| salary.to_float_val() * hours.to_float_val())
+                .sum::<f64>();
+            let hours = cols[1].iter().map(|h| h.to_float_val()).sum::<f64>();
+            Cell::Float(total / hours)
+        })
+        .to_dataframe()
+        .unwrap();
+    assert_eq!(
+        grouped,
+        Dataframe::from_rows(
+            vec!["department", "names", "weighted"],
+            vec![
+                row!("Sales", "Jake, Sally", 325.0),
+                row!("Marketing", "Susie", 200.0),
+            ],
+        )
+        .unwrap()
+    );
+
+    match df
+        .group_by("department")
+        .agg("bonus", "bonus", |col| Cell::Uint(col.len() as u64))
+        .to_dataframe()
+    {
+        Ok(_) => panic!("Missing agg col not detected"),
+        Err(err) => assert_eq!(err.to_string(), "Group agg col `bonus` not found"),
+    }
+    match df
+        .group_by("department")
+        .agg("salary", "mixed", |col| match col.len() {
+            1 => Cell::Int(1),
+            _ => Cell::Str("many".to_string()),
+        })
+        .to_dataframe()
+    {
+        Ok(_) => panic!("Mixed agg types not detected"),
+        Err(err) => assert_eq!(err.to_string(), "Inconsistent col types"),
+    }
+}
+
+#[test]
+fn group_transform() {
+    let df = Dataframe::from_rows(
+        vec!["name", "department", "salary"],
+        vec![
+            row!("Jasper", "Sales", 100),
+            row!("James", "Marketing", 200),
+            row!("Susan", "Sales", 300),
+            row!("Jane", "Marketing", 400),
+            row!("Sam", "Sales", 200),
+        ],
+    )
+    .unwrap();
+    let transformed = df
+        .group_by("department")
+        .select("salary", Mean, "dept mean")
+        .select("name", Count, "dept size")
+        .transform()
+        .unwrap();
+    assert_eq!(
+        transformed,
+        Dataframe::from_rows(
+            vec!["name", "department", "salary", "dept mean", "dept size"],
+            vec![
+                row!("Jasper", "Sales", 100, 200.0, 3_u32),
+                row!("James", "Marketing", 200, 300.0, 2_u32),
+                row!("Susan", "Sales", 300, 200.0, 3_u32),
+                row!("Jane", "Marketing", 400, 300.0, 2_u32),
+                row!("Sam", "Sales", 200, 200.0, 3_u32),
+            ],
+        )
+        .unwrap()
+    );
+    match df
+        .group_by("department")
+        .select("salary", Max, "salary")
+        .transform()
+    {
+        Ok(_) => panic!("Duplicate transform col not detected"),
+        Err(err) => assert_eq!(err.to_string(), "Col names must be unique"),
+    }
+
+    // Rank within department
+    let ranked = df
+        .group_by("department")
+        .apply(|mut group| {
+            group.sort("salary", Desc).unwrap();
+            let len = group.length() as u32;
+            group.add_col("rank", (1..=len).collect()).unwrap();
+            group
+        })
+        .unwrap();
+    assert_eq!(
+        ranked,
+        Dataframe::from_rows(
+            vec!["name", "department", "salary", "rank"],
+            vec![
+                row!("Susan", "Sales", 300, 1_u32),
+                row!("Sam", "Sales", 200, 2_u32),
+                row!("Jasper", "Sales", 100, 3_u32),
+                row!("Jane", "Marketing", 400, 1_u32),
+                row!("James", "Marketing", 200, 2_u32),
+            ],
+        )
+        .unwrap()
+    );
+    match df
+        .group_by("department")
+        .apply(|group| match group.length() {
+            3 => group,
+            _ => Dataframe::new(None),
+        }) {
+        Ok(_) => panic!("Mismatched apply results not detected"),
+        Err(err) => assert_eq!(err.to_string(), "Concat against mismatched dataframes"),
+    }
+}
+
+#[test]
+fn group_having() {
+    let df = Dataframe::from_rows(
+        vec!["name", "department", "salary"],
+        vec![
+            row!("Jasper", "Sales", 100),
+            row!("James", "Marketing", 200),
+            row!("Susan", "Sales", 300),
+            row!("Jane", "Marketing", 400),
+            row!("Sam", "Sales", 200),
+            row!("Sally", "Engineering", 900),
+        ],
+    )
+    .unwrap();
+    // Alias and an aggregate that isn't selected
+    let grouped = df
+        .group_by_cols(vec!["department"])
+        .select("name", Count, "count")
+        .having(exp("count", gt(), 1_u32))
+        .having(exp("max(salary)", lt(), 400))
+        .to_dataframe()
+        .unwrap();
+    assert_eq!(
+        grouped,
+        Dataframe::from_rows(vec!["department", "count"], vec![row!("Sales", 3_u32)],).unwrap()
+    );
+    // Dropping every group, or grouping no rows, still gives typed columns
+    let grouped = df
+        .group_by_cols(vec!["department"])
+        .select("name", Count, "count")
+        .select("salary", Mean, "mean")
+        .select("salary", Max, "max")
+        .having(exp("count", gt(), 5_u32))
+        .to_dataframe()
+        .unwrap();
+    assert_eq!(grouped.length(), 0);
+    assert_eq!(
+        grouped.col_types(),
+        vec![
+            "department <Str>",
+            "count <Uint>",
+            "mean <Float>",
+            "max <Int>"
+        ]
+    );
+    let empty = df.clone().filter(exp("salary", gt(), 1000)).unwrap();
+    let grouped = empty
+        .group_by_cols(vec!["department"])
+        .select("salary", Sum, "total")
+        .to_dataframe()
+        .unwrap();
+    assert_eq!(grouped.col_types(), vec!["department <Str>", "total <Int>"]);
+    let grouped = df
+        .group_by_cols(vec!["department"])
+        .select("salary", Sum, "total")
+        .having(or(vec![
+            exp("department", eq(), "Engineering"),
+            exp("quantile(salary, 0.5)", gte(), 300.0),
+        ]))
+        .to_dataframe()
+        .unwrap();
+    assert_eq!(
+        grouped,
+        Dataframe::from_rows(
+            vec!["department", "total"],
+            vec![row!("Marketing", 600), row!("Engineering", 900)],
+        )
+        .unwrap()
+    );
+    // Rows of dropped groups are dropped from transforms
+    let transformed = df
+        .group_by("department")
+        .select("salary", Max, "dept max")
+        .having(exp("count(name)", gte(), 2_u32))
+        .transform()
+        .unwrap();
+    assert_eq!(
+        transformed,
+        Dataframe::from_rows(
+            vec!["name", "department", "salary", "dept max"],
+            vec![
+                row!("Jasper", "Sales", 100, 300),
+                row!("James", "Marketing", 200, 400),
+                row!("Susan", "Sales", 300, 300),
+                row!("Jane", "Marketing", 400, 400),
+                row!("Sam", "Sales", 200, 300),
+            ],
+        )
+        .unwrap()
+    );
+
+    match df
+        .group_by("department")
+        .having(exp("sum(bonus)", gt(), 0))
+        .to_dataframe()
+    {
+        Ok(_) => panic!("Missing having target not detected"),
+        Err(err) => assert_eq!(err.to_string(), "Having target `sum(bonus)` not found"),
+    }
+}
+
+#[test]
+fn group_rollup() {
+    let df = Dataframe::from_rows(
+        vec!["region", "year", "sales"],
+        vec![
+            row!("East", 2023_u32, 100),
+            row!("East", 2024_u32, 200),
+            row!("West", 2023_u32, 300),
+        ],
+    )
+    .unwrap();
+    let rollup = df
+        .group_by_cols(vec!["region", "year"])
+        .select("sales", Sum, "total")
+        .rollup()
+        .grouping_id("level")
+        .to_dataframe()
+        .unwrap();
+    assert_eq!(
+        rollup,
+        Dataframe::from_rows(
+            vec!["region", "year", "level", "total"],
+            vec![
+                row!(Some("East"), Some(2023_u32), 0_u32, 100),
+                row!(Some("East"), Some(2024_u32), 0_u32, 200),
+                row!(Some("West"), Some(2023_u32), 0_u32, 300),
+                row!(Some("East"), None::<u32>, 1_u32, 300),
+                row!(Some("West"), None::<u32>, 1_u32, 300),
+                row!(None::<&str>, None::<u32>, 3_u32, 600),
+            ],
+        )
+        .unwrap()
+    );
+    assert_eq!(rollup.col_types()[1], "year <Uint>");
+
+    let cube = df
+        .group_by_cols(vec!["region", "year"])
+        .select("sales", Sum, "total")
+        .cube()
+        .grouping_id("level")
+        .having(exp("level", eq(), 2_u32))
+        .to_dataframe()
+        .unwrap();
+    assert_eq!(
+        cube,
+        Dataframe::from_rows(
+            vec!["region", "year", "level", "total"],
+            vec![
+                row!(None::<&str>, Some(2023_u32), 2_u32, 400),
+                row!(None::<&str>, Some(2024_u32), 2_u32, 200),
             ],
         )
         .unwrap()
     );
+
+    let sets = df
+        .group_by("region")
+        .select("sales", Max, "max")
+        .grouping_sets(vec![vec!["region"], vec![]])
+        .to_dataframe()
+        .unwrap();
     assert_eq!(
-        Col::new("val".to_string(), (1..=4).collect::<Vec<u32>>()).describe(),
+        sets,
         Dataframe::from_rows(
-            vec!["::", "val"],
+            vec!["region", "max"],
             vec![
-                row!("count", 4.0),
-                row!("mean", 2.5),
-                row!("std", 1.12),
-                row!("min", 1.0),
-                row!("25%", 1.5),
-                row!("50%", 2.5),
-                row!("75%", 3.5),
-                row!("max", 4.0),
-                row!("unique", None::<f64>),
-                row!("top idx", None::<f64>),
-                row!("freq", None::<f64>),
+                row!(Some("East"), 200),
+                row!(Some("West"), 300),
+                row!(None::<&str>, 300),
             ],
         )
         .unwrap()
     );
+    // a key reselected under its own name keeps the subtotal nulls
+    let rollup = df
+        .group_by("region")
+        .select("region", Coalesce, "region")
+        .select("sales", Sum, "total")
+        .rollup()
+        .to_dataframe()
+        .unwrap();
     assert_eq!(
-        Col::new("val".to_string(), (1..=5).collect::<Vec<u32>>()).describe(),
+        rollup,
         Dataframe::from_rows(
-            vec!["::", "val"],
+            vec!["region", "total"],
             vec![
-                row!("count", 5.0),
-                row!("mean", 3.0),
-                row!("std", 1.41),
-                row!("min", 1.0),
-                row!("25%", 1.5),
-                row!("50%", 3.0),
-                row!("75%", 4.5),
-                row!("max", 5.0),
-                row!("unique", None::<f64>),
-                row!("top idx", None::<f64>),
-                row!("freq", None::<f64>),
+                row!(Some("East"), 300),
+                row!(Some("West"), 300),
+                row!(None::<&str>, 600),
             ],
         )
         .unwrap()
     );
+
+    // the grand total of an empty frame is still a row
+    let empty = df.clone().filter(exp("sales", gt(), 1000)).unwrap();
+    let rollup = empty
+        .group_by_cols(vec!["region", "year"])
+        .select("sales", Sum, "total")
+        .select("sales", Count, "count")
+        .rollup()
+        .grouping_id("level")
+        .to_dataframe()
+        .unwrap();
     assert_eq!(
-        Col::new("val".to_string(), (1..=6).collect::<Vec<u32>>()).describe(),
+        rollup,
         Dataframe::from_rows(
-            vec!["::", "val"],
-            vec![
-                row!("count", 6.0),
-                row!("mean", 3.5),
-                row!("std", 1.71),
-                row!("min", 1.0),
-                row!("25%", 2.0),
-                row!("50%", 3.5),
-                row!("75%", 5.0),
-                row!("max", 6.0),
-                row!("unique", None::<f64>),
-                row!("top idx", None::<f64>),
-                row!("freq", None::<f64>),
-            ],
+            vec!["region", "year", "level", "total", "count"],
+            vec![row!(None::<&str>, None::<u32>, 3_u32, None::<i32>, 0_u32)],
         )
         .unwrap()
     );
+
+    match df
+        .group_by("region")
+        .grouping_sets(vec![vec!["year"]])
+        .to_dataframe()
+    {
+        Ok(_) => panic!("Grouping set outside group by not detected"),
+        Err(err) => assert_eq!(
+            err.to_string(),
+            "Grouping set col `year` is not a group by col"
+        ),
+    }
+}
+
+#[test]
+fn pivot_melt() {
+    let df = Dataframe::from_rows(
+        vec!["region", "year", "sales"],
+        vec![
+            row!("East", 2023_u32, 100),
+            row!("East", 2024_u32, 200),
+            row!("West", 2023_u32, 300),
+            row!("East", 2023_u32, 50),
+        ],
+    )
+    .unwrap();
+    let pivoted = df.pivot("region", "year", "sales", Sum).unwrap();
     assert_eq!(
-        Col::new("val".to_string(), (1..=7).collect::<Vec<u32>>()).describe(),
+        pivoted,
         Dataframe::from_rows(
-            vec!["::", "val"],
+            vec!["region", "2023", "2024"],
+            vec![row!("East", 150, Some(200)), row!("West", 300, None::<i32>)],
+        )
+        .unwrap()
+    );
+    assert_eq!(
+        df.pivot("year", "region", "sales", Count).unwrap(),
+        Dataframe::from_rows(
+            vec!["year", "East", "West"],
             vec![
-                row!("count", 7.0),
-                row!("mean", 4.0),
-                row!("std", 2.0),
-                row!("min", 1.0),
-                row!("25%", 2.0),
-                row!("50%", 4.0),
-                row!("75%", 6.0),
-                row!("max", 7.0),
-                row!("unique", None::<f64>),
-                row!("top idx", None::<f64>),
-                row!("freq", None::<f64>),
+                row!(2023_u32, 2_u32, Some(1_u32)),
+                row!(2024_u32, 1_u32, None::<u32>)
             ],
         )
         .unwrap()
     );
+    // the values column can also be a key
     assert_eq!(
-        Col::new("val".to_string(), (1..=8).collect::<Vec<u32>>()).describe(),
+        df.pivot("region", "year", "year", Count).unwrap(),
         Dataframe::from_rows(
-            vec!["::", "val"],
+            vec!["region", "2023", "2024"],
             vec![
-                row!("count", 8.0),
-                row!("mean", 4.5),
-                row!("std", 2.29),
-                row!("min", 1.0),
-                row!("25%", 2.5),
-                row!("50%", 4.5),
-                row!("75%", 6.5),
-                row!("max", 8.0),
-                row!("unique", None::<f64>),
-                row!("top idx", None::<f64>),
-                row!("freq", None::<f64>),
+                row!("East", 2_u32, Some(1_u32)),
+                row!("West", 1_u32, None::<u32>)
             ],
         )
         .unwrap()
     );
     assert_eq!(
-        Col::new("val".to_string(), (1..=9).collect::<Vec<u32>>()).describe(),
+        df.pivot("region", "year", "region", Count).unwrap(),
+        df.pivot("region", "year", "year", Count).unwrap()
+    );
+    let with_null = Dataframe::from_rows(
+        vec!["region", "year", "sales"],
+        vec![
+            row!("East", Some(2023_u32), 100),
+            row!("West", None::<u32>, 300),
+        ],
+    )
+    .unwrap();
+    match with_null.pivot("region", "year", "sales", Sum) {
+        Ok(_) => panic!("Null pivot header not detected"),
+        Err(err) => assert_eq!(err.to_string(), "Null header value in column `year`"),
+    }
+    match df.pivot("region", "quarter", "sales", Sum) {
+        Ok(_) => panic!("Missing pivot col not detected"),
+        Err(err) => assert_eq!(err.to_string(), "Column not found"),
+    }
+
+    let melted = pivoted.melt(vec!["region"], vec![]).unwrap();
+    assert_eq!(
+        melted,
         Dataframe::from_rows(
-            vec!["::", "val"],
+            vec!["region", "variable", "value"],
             vec![
-                row!("count", 9.0),
-                row!("mean", 5.0),
-                row!("std", 2.58),
-                row!("min", 1.0),
-                row!("25%", 2.5),
-                row!("50%", 5.0),
-                row!("75%", 7.5),
-                row!("max", 9.0),
-                row!("unique", None::<f64>),
-                row!("top idx", None::<f64>),
-                row!("freq", None::<f64>),
+                row!("East", "2023", Some(150)),
+                row!("West", "2023", Some(300)),
+                row!("East", "2024", Some(200)),
+                row!("West", "2024", None::<i32>),
             ],
         )
         .unwrap()
     );
     assert_eq!(
-        Col::new("val".to_string(), (1..=10).collect::<Vec<u32>>()).describe(),
+        pivoted.melt(vec![], vec!["2024"]).unwrap(),
         Dataframe::from_rows(
-            vec!["::", "val"],
+            vec!["variable", "value"],
+            vec![row!("2024", Some(200)), row!("2024", None::<i32>)],
+        )
+        .unwrap()
+    );
+
+    // mixed value types are unified
+    let mixed = Dataframe::from_rows(
+        vec!["region", "count", "share", "note"],
+        vec![
+            row!("East", 2_u32, 0.5, "new"),
+            row!("West", 3_u32, 1.5, "old"),
+        ],
+    )
+    .unwrap();
+    assert_eq!(
+        mixed.melt(vec!["region"], vec!["count", "share"]).unwrap(),
+        Dataframe::from_rows(
+            vec!["region", "variable", "value"],
             vec![
-                row!("count", 10.0),
-                row!("mean", 5.5),
-                row!("std", 2.87),
-                row!("min", 1.0),
-                row!("25%", 3.0),
-                row!("50%", 5.5),
-                row!("75%", 8.0),
-                row!("max", 10.0),
-                row!("unique", None::<f64>),
-                row!("top idx", None::<f64>),
-                row!("freq", None::<f64>),
+                row!("East", "count", 2.0),
+                row!("West", "count", 3.0),
+                row!("East", "share", 0.5),
+                row!("West", "share", 1.5),
             ],
         )
         .unwrap()
     );
     assert_eq!(
-        Col::new("val".to_string(), vec!["A", "B", "C", "B", "C", "B", "Z"]).describe(),
+        mixed.melt(vec!["region"], vec!["count", "note"]).unwrap(),
         Dataframe::from_rows(
-            vec!["::", "val"],
+            vec!["region", "variable", "value"],
             vec![
-                row!("count", 7.0),
-                row!("mean", None::<f64>),
-                row!("std", None::<f64>),
-                row!("min", None::<f64>),
-                row!("25%", None::<f64>),
-                row!("50%", None::<f64>),
-                row!("75%", None::<f64>),
-                row!("max", None::<f64>),
-                row!("unique", 4.0),
-                row!("top idx", 1.0),
-                row!("freq", 3.0),
+                row!("East", "count", "2"),
+                row!("West", "count", "3"),
+                row!("East", "note", "new"),
+                row!("West", "note", "old"),
             ],
         )
         .unwrap()
     );
+    assert_eq!(
+        df.melt(vec!["region"], vec!["year", "sales"])
+            .unwrap()
+            .col_types()[2],
+        "value <Int>"
+    );
 }
 
 #[test]
-fn group() {
+fn transpose() {
     let df = Dataframe::from_rows(
-        vec!["name", "department", "salary", "age"],
+        vec!["setting", "small", "large", "limit"],
         vec![
-            row!("Jasper", "Sales", 100, 29),
-            row!("James", "Marketing", 200, 44),
-            row!("Susan", "Sales", 300, 65),
-            row!("Jane", "Marketing", 400, 47),
-            row!("Sam", "Sales", 100, 55),
-            row!("Sally", "Engineering", 200, 30),
+            row!("alpha", 1, 2.5, 10_u32),
+            row!("beta", 3, Some(4.0), 20_u32),
+            row!("gamma", -1, None::<f64>, u64::MAX),
         ],
     )
     .unwrap();
-    df.to_slice()
-        .chunk_by("department")
-        .unwrap()
-        .into_iter()
-        .enumerate()
-        .for_each(|(i, chunk)| match i {
-            0 => assert_eq!(
-                chunk,
-                Dataframe::from_rows(
-                    vec!["name", "department", "salary", "age"],
-                    vec![
-                        row!("Jasper", "Sales", 100, 29),
-                        row!("Susan", "Sales", 300, 65),
-                        row!("Sam", "Sales", 100, 55),
-                    ],
-                )
-                .unwrap()
-            ),
-            1 => assert_eq!(
-                chunk,
-                Dataframe::from_rows(
-                    vec!["name", "department", "salary", "age"],
-                    vec![
-                        row!("James", "Marketing", 200, 44),
-                        row!("Jane", "Marketing", 400, 47),
-                    ],
-                )
-                .unwrap()
-            ),
-            _ => assert_eq!(
-                chunk,
-                Dataframe::from_rows(
-                    vec!["name", "department", "salary", "age"],
-                    vec![row!("Sally", "Engineering", 200, 30),],
-                )
-                .unwrap()
-            ),
-        });
-    let grouped = df
-        .group_by("department")
-        .select("department", Coalesce, "department")
-        .select("name", Count, "count")
-        .select("salary", Max, "max salary")
-        .select("salary", Min, "min salary")
-        .select("age", Mean, "average age")
-        .to_dataframe()
-        .unwrap();
     assert_eq!(
-        grouped,
+        df.transpose("setting").unwrap(),
         Dataframe::from_rows(
+            vec!["setting", "alpha", "beta", "gamma"],
             vec![
-                "department",
-                "count",
-                "max salary",
-                "min salary",
-                "average age"
+                row!("small", 1.0, 3.0, Some(-1.0)),
+                row!("large", 2.5, 4.0, None::<f64>),
+                row!("limit", 10.0, 20.0, Some(u64::MAX as f64)),
             ],
+        )
+        .unwrap()
+    );
+    // Int and Uint stay Int, anything else falls back to Str
+    let mut df = Dataframe::from_rows(
+        vec!["setting", "count", "size", "enabled"],
+        vec![
+            row!("alpha", -1, 2_u32, Some(true)),
+            row!("beta", 3, 4_u32, None::<bool>),
+        ],
+    )
+    .unwrap();
+    assert_eq!(
+        df.transpose("setting").unwrap(),
+        Dataframe::from_rows(
+            vec!["setting", "alpha", "beta"],
             vec![
-                row!("Sales", 3 as u32, 300.0, 100.0, 49.67),
-                row!("Marketing", 2 as u32, 400.0, 200.0, 45.5),
-                row!("Engineering", 1 as u32, 200.0, 200.0, 30.0),
+                row!("count", Some("-1"), Some("3")),
+                row!("size", Some("2"), Some("4")),
+                row!("enabled", Some("true"), None::<&str>),
             ],
         )
         .unwrap()
-    )
-}
+    );
+    df.retain_cols(["setting", "count", "size"].into());
+    assert_eq!(
+        df.transpose("setting").unwrap().col_types(),
+        vec!["setting <Str>", "alpha <Int>", "beta <Int>"]
+    );
 
-#[derive(Deserialize)]
-struct SneakyType {
-    name: String,
-    age: i64,
-    val: bool,
-}
-impl ToRow for SneakyType {
-    fn to_row(&self) -> Vec<Cell> {
-        if self.age < 40 {
-            vec![self.name.as_str().into(), self.age.into(), self.val.into()]
-        } else {
-            vec![
-                self.name.as_str().into(),
-                self.age.into(),
-                format!("{}", self.val).into(),
-            ]
-        }
+    match Dataframe::from_rows(
+        vec!["setting", "val"],
+        vec![row!("alpha", 1), row!("alpha", 2)],
+    )
+    .unwrap()
+    .transpose("setting")
+    {
+        Ok(_) => panic!("Duplicate headers not detected"),
+        Err(err) => assert_eq!(err.to_string(), "Col names must be unique"),
     }
-    fn labels(&self) -> Vec<String> {
-        vec!["name".to_string(), "age".to_string(), "val".to_string()]
+    match Dataframe::from_rows(
+        vec!["setting", "val"],
+        vec![row!(Some("Null"), 1), row!(None::<&str>, 2)],
+    )
+    .unwrap()
+    .transpose("setting")
+    {
+        Ok(_) => panic!("Null header not detected"),
+        Err(err) => assert_eq!(err.to_string(), "Null header value in column `setting`"),
     }
 }
+
 #[test]
 fn mismatched_types() {
     // Not exposed: add_cell_col / set_columns
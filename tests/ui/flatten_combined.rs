@@ -0,0 +1,9 @@
+use rowboat::dataframe::*;
+
+#[derive(FromRow)]
+struct Row {
+    #[rowboat(flatten, rename = "id")]
+    a: i32,
+}
+
+fn main() {}
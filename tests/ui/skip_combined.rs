@@ -0,0 +1,11 @@
+use rowboat::dataframe::*;
+
+#[derive(ToRow)]
+struct Row {
+    #[rowboat(skip, rename = "id")]
+    a: i32,
+    #[rowboat(skip, flatten)]
+    b: i32,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use rowboat::dataframe::*;
+
+#[derive(ToRow)]
+struct Row {
+    #[rowboat(renamed = "id")]
+    a: i32,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use rowboat::dataframe::*;
+
+#[derive(ToRow)]
+enum Row {
+    A(i32),
+}
+
+#[derive(FromRow)]
+struct Unit;
+
+fn main() {}